@@ -0,0 +1,358 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use crate::error::AppError;
+use crate::host::{Host, KvStore};
+
+// 多账号：同一台电脑可保存多个已登录账号并快速切换。
+// 每个账号的 token 与云端 deviceId 存在 "account:<id>:" 命名空间下；没有激活账号时
+// （daemon、旧版本升级上来）读写无前缀的键。
+// 登录完成前还不知道账号 id 时（扫码登录、响应里没有 uid），新 token 写在 "pending:" 下，
+// 不动当前账号；确定账号后再迁入它的命名空间。
+//
+// 挖矿收益归属：同一时刻只有"当前激活账号"在挖。XMRig 的 rig-id 由该账号名下本机的
+// deviceId 派生（见 miner），心跳也以这台设备上报算力；切换前提交的份额记在旧账号，
+// 切换并重启矿工后记在新账号。
+
+const KEY_ACCOUNTS: &str = "accounts";
+const KEY_ACTIVE: &str = "active_account";
+const PENDING_PREFIX: &str = "pending:";
+// 随账号切换的键（设备名、指纹属于本机，不随账号变化）
+const SCOPED_KEYS: &[&str] = &["access_token", "refresh_token", "auth_token", "device_id", "mining_credentials"];
+// 其中的登录凭证
+const TOKEN_KEYS: &[&str] = &["access_token", "refresh_token", "auth_token"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 登录邮箱；找回密码时据此确定要清除哪个账号的凭证
+    #[serde(default)]
+    pub email: Option<String>,
+    /// 仅返回给前端时填充
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub active: bool,
+}
+
+fn prefix(id: &str) -> String {
+    format!("account:{}:", id)
+}
+
+fn active_id(host: &Host) -> Option<String> {
+    host.get_str(KEY_ACTIVE)
+}
+
+fn load(host: &Host) -> Vec<Account> {
+    host.store()
+        .get(KEY_ACCOUNTS)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save(host: &Host, accounts: &[Account]) -> Result<(), AppError> {
+    host.store().set(KEY_ACCOUNTS, serde_json::to_value(accounts)?)
+}
+
+fn set_active(host: &Host, id: Option<&str>) -> Result<(), AppError> {
+    match id {
+        Some(id) => host.store().set(KEY_ACTIVE, id.into()),
+        None => host.store().delete(KEY_ACTIVE),
+    }
+}
+
+/// 把 key 映射到当前账号的命名空间；没有激活账号时原样返回
+pub fn scoped(host: &Host, key: &str) -> String {
+    match active_id(host) {
+        Some(id) => format!("{}{}", prefix(&id), key),
+        None => key.to_string(),
+    }
+}
+
+/// 已保存的账号，当前账号带 active 标记
+pub fn list(host: &Host) -> Vec<Account> {
+    let active = active_id(host);
+    let mut accounts = load(host);
+    for a in accounts.iter_mut() {
+        a.active = active.as_deref() == Some(a.id.as_str());
+    }
+    accounts
+}
+
+pub fn active(host: &Host) -> Option<Account> {
+    list(host).into_iter().find(|a| a.active)
+}
+
+// 无前缀的键属于刚登录完成的这个账号（旧版本的单账号数据），
+// 迁入它的命名空间；目标已有值时以目标为准
+fn adopt_unscoped(host: &Host, id: &str) -> Result<(), AppError> {
+    let store = host.store();
+    for key in SCOPED_KEYS {
+        if let Some(v) = store.get(key) {
+            let dst = format!("{}{}", prefix(id), key);
+            if store.get(&dst).is_none() {
+                store.set(&dst, v)?;
+            }
+            store.delete(key)?;
+        }
+    }
+    Ok(())
+}
+
+/// 登录成功后调用：记录账号（已存在则更新名称与邮箱）并设为当前账号。
+/// 之后写入的 token 即落在该账号的命名空间下。
+pub fn activate(host: &Host, id: &str, name: Option<&str>, email: Option<&str>) -> Result<Account, AppError> {
+    let mut accounts = load(host);
+    match accounts.iter_mut().find(|a| a.id == id) {
+        Some(a) => {
+            if let Some(n) = name {
+                a.name = Some(n.to_string());
+            }
+            if let Some(e) = email {
+                a.email = Some(e.to_string());
+            }
+        }
+        None => accounts.push(Account {
+            id: id.to_string(),
+            name: name.map(|n| n.to_string()),
+            email: email.map(|e| e.to_string()),
+            active: false,
+        }),
+    }
+    save(host, &accounts)?;
+    adopt_unscoped(host, id)?;
+    set_active(host, Some(id))?;
+    log::info!("account {} active", id);
+    active(host).ok_or(AppError::InvalidInput("accountId"))
+}
+
+/// 待定登录的存储视图：账号相关的键读写 "pending:" 命名空间，且视为没有激活账号；
+/// 其余键（设备名、版本门槛等）直通
+struct PendingStore(Arc<dyn KvStore>);
+
+impl PendingStore {
+    fn map(key: &str) -> String {
+        if SCOPED_KEYS.contains(&key) { format!("{}{}", PENDING_PREFIX, key) } else { key.to_string() }
+    }
+}
+
+impl KvStore for PendingStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        if key == KEY_ACTIVE { None } else { self.0.get(&Self::map(key)) }
+    }
+    fn set(&self, key: &str, value: Value) -> Result<(), AppError> {
+        self.0.set(&Self::map(key), value)
+    }
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.0.delete(&Self::map(key))
+    }
+    fn keys(&self) -> Vec<String> {
+        self.0.keys()
+    }
+}
+
+/// 登录完成前还不知道账号 id 时调用（扫码登录轮询可能持续数分钟）：返回一个 Host 视图，
+/// 经由它写入的 token 落在 "pending:" 下。当前账号保持激活，心跳、挖矿不受影响；
+/// 确定账号后用 `finish_pending` 迁入，失败时用 `discard_pending` 丢弃。
+/// 上次中途退出残留的待定数据在这里清掉。
+pub fn begin_pending(host: &Host) -> Result<Host, AppError> {
+    discard_pending(host)?;
+    Ok(host.with_store(|store| Arc::new(PendingStore(store))))
+}
+
+/// 把待定的 token 迁入账号 id 的命名空间（替换该账号原有的凭证）并设为当前账号
+pub fn finish_pending(host: &Host, id: &str, name: Option<&str>, email: Option<&str>) -> Result<Account, AppError> {
+    let store = host.store();
+    for key in SCOPED_KEYS {
+        let src = format!("{}{}", PENDING_PREFIX, key);
+        let dst = format!("{}{}", prefix(id), key);
+        match store.get(&src) {
+            Some(v) => {
+                store.set(&dst, v)?;
+                store.delete(&src)?;
+            }
+            // 新会话没有的凭证（如 refresh token）不能沿用旧会话的
+            None if TOKEN_KEYS.contains(key) => store.delete(&dst)?,
+            None => {}
+        }
+    }
+    activate(host, id, name, email)
+}
+
+/// 丢弃待定登录的数据，当前账号不受影响
+pub fn discard_pending(host: &Host) -> Result<(), AppError> {
+    for key in host.store().keys() {
+        if key.starts_with(PENDING_PREFIX) {
+            host.store().delete(&key)?;
+        }
+    }
+    Ok(())
+}
+
+/// 按登录邮箱查找已保存的账号（不区分大小写）
+pub fn find_by_email(host: &Host, email: &str) -> Option<Account> {
+    let email = email.trim();
+    list(host)
+        .into_iter()
+        .find(|a| a.email.as_deref().is_some_and(|e| e.eq_ignore_ascii_case(email)))
+}
+
+/// 清除指定账号的登录凭证（密码被重置后服务端已作废），账号本身与 deviceId 保留
+pub fn clear_credentials(host: &Host, id: &str) -> Result<(), AppError> {
+    for key in TOKEN_KEYS {
+        host.store().delete(&format!("{}{}", prefix(id), key))?;
+    }
+    Ok(())
+}
+
+/// 切换到已保存的账号
+pub fn switch(host: &Host, id: &str) -> Result<Account, AppError> {
+    if !load(host).iter().any(|a| a.id == id) {
+        return Err(AppError::InvalidInput("accountId"));
+    }
+    set_active(host, Some(id))?;
+    log::info!("switched to account {}", id);
+    active(host).ok_or(AppError::InvalidInput("accountId"))
+}
+
+/// 删除账号及其命名空间下的全部数据。删除的是当前账号时切到剩下的第一个，
+/// 返回删除后的当前账号。
+pub fn remove(host: &Host, id: &str) -> Result<Option<Account>, AppError> {
+    let mut accounts = load(host);
+    accounts.retain(|a| a.id != id);
+    save(host, &accounts)?;
+    let ns = prefix(id);
+    for key in host.store().keys() {
+        if key.starts_with(&ns) {
+            host.store().delete(&key)?;
+        }
+    }
+    if active_id(host).as_deref() == Some(id) {
+        set_active(host, accounts.first().map(|a| a.id.as_str()))?;
+    }
+    log::info!("account {} removed", id);
+    Ok(active(host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use serde_json::json;
+
+    #[test]
+    fn activate_scopes_keys_and_adopts_legacy_data() {
+        let (host, _) = testutil::host();
+        assert_eq!(scoped(&host, "access_token"), "access_token");
+        // 旧版本的单账号数据迁入第一个登录的账号
+        host.store().set("access_token", json!("legacy")).unwrap();
+        let account = activate(&host, "u1", Some("alice"), Some("alice@example.com")).unwrap();
+        assert!(account.active);
+        assert_eq!(scoped(&host, "access_token"), "account:u1:access_token");
+        assert_eq!(host.get_str("account:u1:access_token").as_deref(), Some("legacy"));
+        assert!(host.store().get("access_token").is_none());
+
+        // 再次登录只更新名称，不重复登记
+        activate(&host, "u1", Some("alice2"), None).unwrap();
+        let all = list(&host);
+        assert_eq!(all.len(), 1);
+        assert_eq!((all[0].name.as_deref(), all[0].email.as_deref()), (Some("alice2"), Some("alice@example.com")));
+    }
+
+    #[test]
+    fn switch_changes_the_active_namespace() {
+        let (host, _) = testutil::host();
+        activate(&host, "u1", None, None).unwrap();
+        activate(&host, "u2", None, None).unwrap();
+        assert_eq!(active(&host).unwrap().id, "u2");
+
+        assert_eq!(switch(&host, "u1").unwrap().id, "u1");
+        assert_eq!(scoped(&host, "device_id"), "account:u1:device_id");
+        assert_eq!(list(&host).iter().filter(|a| a.active).count(), 1);
+        assert!(matches!(switch(&host, "nobody"), Err(AppError::InvalidInput(_))));
+        assert_eq!(active(&host).unwrap().id, "u1");
+    }
+
+    #[test]
+    fn removing_active_account_falls_back_to_first() {
+        let (host, _) = testutil::host();
+        for id in ["u1", "u2", "u3"] {
+            activate(&host, id, None, None).unwrap();
+            host.store().set(&format!("account:{}:access_token", id), json!(id)).unwrap();
+        }
+        // 删除非当前账号不影响当前账号
+        assert_eq!(remove(&host, "u2").unwrap().unwrap().id, "u3");
+        assert!(host.store().get("account:u2:access_token").is_none());
+
+        assert_eq!(remove(&host, "u3").unwrap().unwrap().id, "u1");
+        assert!(host.store().get("account:u3:access_token").is_none());
+        assert_eq!(host.get_str("account:u1:access_token").as_deref(), Some("u1"));
+
+        assert!(remove(&host, "u1").unwrap().is_none());
+        assert!(list(&host).is_empty());
+        assert_eq!(scoped(&host, "access_token"), "access_token");
+    }
+
+    #[test]
+    fn pending_login_leaves_active_account_alone() {
+        let (host, _) = testutil::host();
+        activate(&host, "u1", None, None).unwrap();
+        host.store().set("account:u1:access_token", json!("a1")).unwrap();
+        host.store().set("account:u1:device_id", json!("dev-1")).unwrap();
+
+        let pending = begin_pending(&host).unwrap();
+        pending.store().set(&scoped(&pending, "access_token"), json!("new")).unwrap();
+        // 轮询期间当前账号照常工作
+        assert_eq!(active(&host).unwrap().id, "u1");
+        assert_eq!(host.get_str(&scoped(&host, "access_token")).as_deref(), Some("a1"));
+        assert_eq!(host.get_str("pending:access_token").as_deref(), Some("new"));
+        assert!(pending.get_str(&scoped(&pending, "device_id")).is_none());
+
+        // 失败：丢弃待定 token，什么都不变
+        discard_pending(&host).unwrap();
+        assert!(host.store().get("pending:access_token").is_none());
+        assert_eq!(active(&host).unwrap().id, "u1");
+        assert_eq!(host.get_str("account:u1:access_token").as_deref(), Some("a1"));
+    }
+
+    #[test]
+    fn finished_pending_login_moves_tokens_into_the_account() {
+        let (host, _) = testutil::host();
+        activate(&host, "u1", None, None).unwrap();
+        host.store().set("account:u2:refresh_token", json!("stale")).unwrap();
+        // 上次中途退出的残留在开始时清掉
+        host.store().set("pending:refresh_token", json!("leftover")).unwrap();
+
+        let pending = begin_pending(&host).unwrap();
+        assert!(host.store().get("pending:refresh_token").is_none());
+        pending.store().set(&scoped(&pending, "access_token"), json!("a2")).unwrap();
+        let account = finish_pending(&host, "u2", Some("bob"), Some("bob@example.com")).unwrap();
+
+        assert_eq!(account.id, "u2");
+        assert_eq!(host.get_str("account:u2:access_token").as_deref(), Some("a2"));
+        assert!(host.store().get("account:u2:refresh_token").is_none());
+        assert!(host.store().keys().iter().all(|k| !k.starts_with(PENDING_PREFIX)));
+        assert_eq!(list(&host).len(), 2);
+    }
+
+    #[test]
+    fn clears_credentials_of_the_matching_account_only() {
+        let (host, _) = testutil::host();
+        activate(&host, "u1", Some("alice"), Some("alice@example.com")).unwrap();
+        host.store().set("account:u1:access_token", json!("a1")).unwrap();
+        activate(&host, "u2", Some("bob"), Some("bob@example.com")).unwrap();
+        host.store().set("account:u2:access_token", json!("a2")).unwrap();
+        host.store().set("account:u2:device_id", json!("dev-2")).unwrap();
+
+        assert!(find_by_email(&host, "nobody@example.com").is_none());
+        let account = find_by_email(&host, " Alice@Example.com ").unwrap();
+        assert_eq!(account.id, "u1");
+        clear_credentials(&host, &account.id).unwrap();
+        assert!(host.store().get("account:u1:access_token").is_none());
+        // 当前账号不受影响
+        assert_eq!(active(&host).unwrap().id, "u2");
+        assert_eq!(host.get_str("account:u2:access_token").as_deref(), Some("a2"));
+        assert_eq!(host.get_str("account:u2:device_id").as_deref(), Some("dev-2"));
+    }
+}
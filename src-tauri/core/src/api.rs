@@ -0,0 +1,809 @@
+use serde::{Deserialize, Serialize};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client, Method, StatusCode};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::accounts;
+use crate::host::Host;
+use crate::version;
+
+
+const KEY_ACCESS: &str = "access_token";
+const KEY_REFRESH: &str = "refresh_token";
+// Written by the email login/register commands, which only receive a single token
+const KEY_AUTH: &str = "auth_token";
+
+
+/// Non-200 HTTP status from one of the authenticated helpers.  Kept as a
+/// typed error so callers can `downcast_ref` and react to specific statuses
+/// (e.g. 404 for a device the server no longer knows about).
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub method: Method,
+    pub status: StatusCode,
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} http {}", self.method.as_str().to_lowercase(), self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Re-establishes a session from scratch (e.g. the daemon logging in again
+/// with its configured credentials) and stores the new token.
+pub type Reauth = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Api {
+    pub base: String,
+    pub client: Client,
+    reauth: Option<Reauth>,
+    versions: Option<version::VersionGate>,
+}
+
+impl Api {
+    pub fn new(base: String) -> Self {
+        // 每个请求都带上客户端与协议版本，服务端据此返回版本门槛
+        let mut headers = HeaderMap::new();
+        headers.insert(version::HEADER_CLIENT_VERSION, HeaderValue::from_static(version::CLIENT_VERSION));
+        headers.insert(version::HEADER_PROTOCOL_VERSION, HeaderValue::from(version::PROTOCOL_VERSION));
+        let client = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        Self { base, client, reauth: None, versions: None }
+    }
+
+    /// Install a fallback used when the session can no longer be refreshed
+    /// (no refresh token, or the server rejected it).  Unattended clients
+    /// use it to log in again instead of running under a dead session.
+    pub fn with_reauth(mut self, reauth: Reauth) -> Self {
+        self.reauth = Some(reauth);
+        self
+    }
+
+    /// Record the client version thresholds carried by every response in
+    /// `gate` (persisted on its host, and the miner stopped when the minimum
+    /// rises above this build).
+    pub fn with_version_gate(mut self, gate: version::VersionGate) -> Self {
+        self.versions = Some(gate);
+        self
+    }
+
+    /// The same client and hooks against another backend base URL.
+    pub fn rebase(&self, base: String) -> Self {
+        Self { base, ..self.clone() }
+    }
+
+    /// Check a response's headers for version thresholds.  Every request
+    /// helper, including the ones in other modules, funnels through here.
+    pub(crate) fn observe_version(&self, headers: &HeaderMap) {
+        if let Some(gate) = &self.versions {
+            gate.observe(headers);
+        }
+    }
+
+    fn observe_version_fields(&self, minimum: Option<&str>, recommended: Option<&str>) {
+        if let Some(gate) = &self.versions {
+            gate.observe_fields(minimum, recommended);
+        }
+    }
+
+    async fn reauthenticate(&self) -> bool {
+        let Some(reauth) = &self.reauth else { return false };
+        match reauth().await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("re-authentication failed: {:#}", e);
+                false
+            }
+        }
+    }
+
+    async fn tokens(host: &Host) -> (Option<String>, Option<String>) {
+        let access = host
+            .get_str(&accounts::scoped(host, KEY_ACCESS))
+            .or_else(|| host.get_str(&accounts::scoped(host, KEY_AUTH)));
+        let refresh = host.get_str(&accounts::scoped(host, KEY_REFRESH));
+        (access, refresh)
+    }
+
+    /// Current access token from the local store, if any.  Used by the
+    /// WebSocket control channel, which authenticates outside of reqwest.
+    pub async fn access_token(host: &Host) -> Option<String> {
+        Self::tokens(host).await.0
+    }
+
+    /// Persist the provided access and refresh tokens to the local store.  If
+    /// the store cannot be written the failure is logged and otherwise
+    /// ignored.
+    pub(crate) async fn set_tokens(host: &Host, access: &str, refresh: &str) {
+        let store = host.store();
+        if let Err(e) = store
+            .set(&accounts::scoped(host, KEY_ACCESS), access.into())
+            .and_then(|_| store.set(&accounts::scoped(host, KEY_REFRESH), refresh.into()))
+        {
+            log::warn!("failed to persist tokens: {}", e);
+        }
+    }
+
+    /// Persist an access token issued without a refresh token, dropping any
+    /// refresh token left over from a previous session.
+    pub(crate) async fn set_access_token(host: &Host, access: &str) {
+        let store = host.store();
+        if let Err(e) = store
+            .set(&accounts::scoped(host, KEY_ACCESS), access.into())
+            .and_then(|_| store.delete(&accounts::scoped(host, KEY_REFRESH)))
+        {
+            log::warn!("failed to persist tokens: {}", e);
+        }
+    }
+
+    /// Persist the single token returned by the email login/register
+    /// endpoints (see `KEY_AUTH`).
+    pub async fn set_auth_token(host: &Host, token: &str) -> Result<(), crate::error::AppError> {
+        host.store().set(&accounts::scoped(host, KEY_AUTH), token.into())
+    }
+
+    /// Remove the access/refresh tokens from the local store, e.g. after a
+    /// password reset invalidated them server-side.
+    pub async fn clear_tokens(host: &Host) {
+        let _ = host.store().delete(&accounts::scoped(host, KEY_ACCESS));
+        let _ = host.store().delete(&accounts::scoped(host, KEY_REFRESH));
+    }
+
+    /// Perform a username/password login.  This helper posts to the
+    /// `/api/v1/auth/login` endpoint and stores the returned access/refresh
+    /// tokens on success.
+    pub async fn login(
+        &self,
+        host: &Host,
+        user: &str,
+        pass: &str,
+    ) -> anyhow::Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LoginReq<'a> {
+            user_name: &'a str,
+            user_password: &'a str,
+        }
+        #[derive(Deserialize)]
+        struct LoginResp {
+            code: i32,
+            data: Option<AuthVo>,
+        }
+        #[derive(Deserialize)]
+        struct AuthVo {
+            #[serde(rename = "accessToken")]
+            access_token: String,
+            #[serde(rename = "refreshToken")]
+            refresh_token: String,
+        }
+
+        let url = format!("{}/api/v1/auth/login", self.base);
+        let resp = self
+            .client
+            .post(url)
+            .json(&LoginReq {
+                user_name: user,
+                user_password: pass,
+            })
+            .send()
+            .await?;
+        self.observe_version(resp.headers());
+        if resp.status() != StatusCode::OK {
+            anyhow::bail!("login http {}", resp.status());
+        }
+        let body: LoginResp = resp.json().await?;
+        if body.code != 0 {
+            anyhow::bail!("login failed: code {}", body.code);
+        }
+        if let Some(d) = body.data {
+            Self::set_tokens(host, &d.access_token, &d.refresh_token).await;
+            Ok(())
+        } else {
+            anyhow::bail!("login empty data");
+        }
+    }
+
+    /// Exchange the refresh token for a new access/refresh pair, persisting
+    /// the result.  Returns the new access token on success.
+    async fn refresh(&self, host: &Host, refresh: &str) -> anyhow::Result<Option<String>> {
+        #[derive(Serialize)]
+        struct R<'a> {
+            #[serde(rename = "refreshToken")]
+            refresh_token: &'a str,
+        }
+        #[derive(Deserialize)]
+        struct RR {
+            code: i32,
+            data: Option<AuthVo>,
+        }
+        #[derive(Deserialize)]
+        struct AuthVo {
+            #[serde(rename = "accessToken")]
+            access_token: String,
+            #[serde(rename = "refreshToken")]
+            refresh_token: String,
+        }
+        let rurl = format!("{}/api/v1/auth/refresh", self.base);
+        let rresp = self
+            .client
+            .post(rurl)
+            .json(&R {
+                refresh_token: refresh,
+            })
+            .send()
+            .await?;
+        self.observe_version(rresp.headers());
+        if rresp.status() == StatusCode::OK {
+            if let Ok(RR { code: 0, data: Some(d) }) = rresp.json().await {
+                Self::set_tokens(host, &d.access_token, &d.refresh_token).await;
+                return Ok(Some(d.access_token));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Refresh the stored session using the stored refresh token, for callers
+    /// that authenticate outside `auth_request` (e.g. the WebSocket handshake).
+    /// Returns `false` when there is no refresh token or the server rejects it.
+    pub(crate) async fn refresh_session(&self, host: &Host) -> anyhow::Result<bool> {
+        let refreshed = match Self::tokens(host).await.1 {
+            Some(r) => self.refresh(host, &r).await?.is_some(),
+            None => false,
+        };
+        Ok(refreshed || self.reauthenticate().await)
+    }
+
+    /// Shared implementation of the authenticated helpers below.  The
+    /// request will include a bearer token if one is available.  If the
+    /// server responds with `401 Unauthorized` and a refresh token exists, a
+    /// refresh will be attempted automatically and the request retried once;
+    /// without a usable refresh token the `Reauth` fallback is tried instead.
+    async fn auth_request<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> anyhow::Result<T> {
+        let (mut access, refresh) = Self::tokens(host).await;
+        let url = format!("{}{}", self.base, path);
+        let build = |access: &Option<String>| {
+            let mut req = self.client.request(method.clone(), &url);
+            if let Some(a) = access {
+                req = req.bearer_auth(a);
+            }
+            if let Some(b) = body {
+                req = req.json(b);
+            }
+            req
+        };
+        let mut resp = build(&access).send().await?;
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            let refreshed = match refresh {
+                Some(r) => self.refresh(host, &r).await?,
+                None => None,
+            };
+            match refreshed {
+                Some(a) => {
+                    access = Some(a);
+                    resp = build(&access).send().await?;
+                }
+                None if self.reauthenticate().await => {
+                    access = Self::tokens(host).await.0;
+                    resp = build(&access).send().await?;
+                }
+                // 保持原有行为：有 refresh token 但换不到新 token 时仍重试一次
+                None if Self::tokens(host).await.1.is_some() => resp = build(&access).send().await?,
+                None => {}
+            }
+        }
+        self.observe_version(resp.headers());
+        if resp.status() != StatusCode::OK {
+            return Err(HttpStatusError { method, status: resp.status() }.into());
+        }
+        Ok(resp.json::<T>().await?)
+    }
+
+    /// Perform an authenticated GET request (see `auth_request`).
+    pub async fn auth_get<T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+    ) -> anyhow::Result<T> {
+        self.auth_request::<(), T>(host, Method::GET, path, None).await
+    }
+
+    /// Perform an authenticated POST request (see `auth_request`).
+    pub async fn auth_post<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+        body: &B,
+    ) -> anyhow::Result<T> {
+        self.auth_request(host, Method::POST, path, Some(body)).await
+    }
+
+    /// Perform an authenticated PUT request (see `auth_request`).
+    pub async fn auth_put<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+        body: &B,
+    ) -> anyhow::Result<T> {
+        self.auth_request(host, Method::PUT, path, Some(body)).await
+    }
+
+    /// Perform an authenticated DELETE request (see `auth_request`).
+    pub async fn auth_delete<T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+    ) -> anyhow::Result<T> {
+        self.auth_request::<(), T>(host, Method::DELETE, path, None).await
+    }
+}
+
+/// A high‑level API client used by the Tauri commands and the daemon.  This wrapper reads
+/// the backend base URL from the `API_BASE_URL` environment variable (falling
+/// back to an empty string) and exposes login, registration and code
+/// operations that return strongly‑typed model structs or `AppError`s.
+pub struct ApiClient {
+    api: Api,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiClient {
+    /// Construct a new `ApiClient` using the `API_BASE_URL` environment
+    /// variable as the backend base URL.  If the variable is unset the base
+    /// defaults to an empty string, meaning relative paths will be used.
+    pub fn new() -> Self {
+        Self::with_base(std::env::var("API_BASE_URL").unwrap_or_default())
+    }
+
+    /// Construct an `ApiClient` for an explicit backend base URL (the
+    /// daemon reads it from its config file instead of the environment).
+    pub fn with_base(base: String) -> Self {
+        Self {
+            api: Api::new(base),
+        }
+    }
+
+    /// Record version thresholds from every response (see
+    /// `Api::with_version_gate`).
+    pub fn with_version_gate(self, gate: crate::version::VersionGate) -> Self {
+        Self {
+            api: self.api.with_version_gate(gate),
+        }
+    }
+
+    /// The underlying token-aware `Api`, for commands that need the
+    /// authenticated helpers (device management, heartbeat, ...).
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+
+    /// Decode a backend response, checking both the HTTP status and the
+    /// business `code` of the `ApiResponse` envelope.  A non-zero business
+    /// code wins over the HTTP status because it is more specific; when the
+    /// body cannot be parsed the HTTP status decides.  Returns the (possibly
+    /// absent) `data` payload on success.
+    async fn decode<T: for<'de> Deserialize<'de>>(
+        &self,
+        resp: reqwest::Response,
+    ) -> Result<Option<T>, crate::error::AppError> {
+        use crate::error::AppError;
+        use crate::models::ApiResponse;
+        let status = resp.status();
+        self.api.observe_version(resp.headers());
+        let body: Result<ApiResponse<T>, _> = resp.json().await;
+        if let Ok(b) = &body {
+            self.api.observe_version_fields(b.min_client_version.as_deref(), b.recommended_client_version.as_deref());
+        }
+        match body {
+            Ok(b) if b.code != 0 => Err(AppError::from_api_code(b.code, &b.message)),
+            Ok(b) if status.is_success() => Ok(b.data),
+            Ok(b) => Err(AppError::from_api_code(status.as_u16() as i32, &b.message)),
+            Err(_) if !status.is_success() => Err(AppError::from_api_code(
+                status.as_u16() as i32,
+                status.canonical_reason().unwrap_or_default(),
+            )),
+            Err(e) => Err(AppError::JsonParseError(Box::new(e))),
+        }
+    }
+
+    /// POST `payload` to `path` and decode the response with `decode`.
+    async fn post<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        payload: &B,
+    ) -> Result<Option<T>, crate::error::AppError> {
+        let url = format!("{}{}", self.api.base, path);
+        let resp = self
+            .api
+            .client
+            .post(&url)
+            .json(payload)
+            .send()
+            .await
+            .map_err(crate::error::AppError::NetworkError)?;
+        self.decode(resp).await
+    }
+
+    /// Like `post`, but a successful response must carry `data`.
+    async fn post_data<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        payload: &B,
+    ) -> Result<T, crate::error::AppError> {
+        self.post(path, payload)
+            .await?
+            .ok_or_else(|| crate::error::AppError::ApiError("No data".to_string()))
+    }
+
+    /// Unwrap the `ApiResponse` envelope returned by one of the
+    /// authenticated `Api` helpers, mapping a non-zero business code the same
+    /// way `decode` does.
+    fn envelope<T>(&self, body: crate::models::ApiResponse<T>) -> Result<Option<T>, crate::error::AppError> {
+        self.api.observe_version_fields(body.min_client_version.as_deref(), body.recommended_client_version.as_deref());
+        if body.code != 0 {
+            return Err(crate::error::AppError::from_auth_code(body.code, &body.message));
+        }
+        Ok(body.data)
+    }
+
+    /// Authenticated GET returning the envelope's `data`.
+    pub(crate) async fn auth_get<T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+    ) -> Result<Option<T>, crate::error::AppError> {
+        self.envelope(self.api.auth_get(host, path).await?)
+    }
+
+    /// Authenticated POST returning the envelope's `data`.
+    async fn auth_post<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+        payload: &B,
+    ) -> Result<Option<T>, crate::error::AppError> {
+        self.envelope(self.api.auth_post(host, path, payload).await?)
+    }
+
+    /// Authenticated PUT returning the envelope's `data`.
+    async fn auth_put<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        host: &Host,
+        path: &str,
+        payload: &B,
+    ) -> Result<Option<T>, crate::error::AppError> {
+        self.envelope(self.api.auth_put(host, path, payload).await?)
+    }
+
+    /// Log in with an email and password.  On success the server returns a
+    /// `UserLoginVO` which may contain a token used for subsequent
+    /// authenticated requests.  Network and deserialisation failures are
+    /// converted into appropriate `AppError` variants.
+    pub async fn login(
+        &self,
+        payload: &crate::models::UserLoginDTO,
+    ) -> Result<crate::models::UserLoginVO, crate::error::AppError> {
+        self.post_data("/api/v1/auth/login", payload).await
+    }
+
+    /// Log in with an email verification code.  The code is posted to
+    /// `/api/v1/auth/login-by-code` and the resulting `UserLoginVO` is
+    /// returned on success.
+    pub async fn login_by_code(
+        &self,
+        payload: &crate::models::EmailCodeLoginDTO,
+    ) -> Result<crate::models::UserLoginVO, crate::error::AppError> {
+        self.post_data("/api/v1/auth/login-by-code", payload).await
+    }
+
+    /// Register a new user.  Posts the `UserRegisterDTO` to the
+    /// `/api/v1/auth/register` endpoint and returns the resulting
+    /// `UserLoginVO`.  All errors are mapped into `AppError`s.
+    pub async fn register(
+        &self,
+        payload: &crate::models::UserRegisterDTO,
+    ) -> Result<crate::models::UserLoginVO, crate::error::AppError> {
+        self.post_data("/api/v1/auth/register", payload).await
+    }
+
+    /// Reset the account password with an email code obtained through
+    /// `send_code` with the `RESET` type.
+    pub async fn reset_password(
+        &self,
+        payload: &crate::models::ResetPasswordDTO,
+    ) -> Result<(), crate::error::AppError> {
+        self.post::<_, serde_json::Value>("/api/v1/auth/reset-password", payload)
+            .await
+            .map(|_| ())
+    }
+
+    /// Complete a login that returned a second-factor challenge.  `code` is
+    /// either a TOTP code or one of the account's recovery codes.
+    pub async fn verify_2fa(
+        &self,
+        payload: &crate::models::TwoFactorVerifyDTO,
+    ) -> Result<crate::models::UserLoginVO, crate::error::AppError> {
+        self.post_data("/api/v1/auth/2fa/verify", payload).await
+    }
+
+    /// Exchange an admin-issued enrollment token for device-scoped
+    /// credentials.  The call is unauthenticated; the token is the proof.
+    pub async fn enroll_device(
+        &self,
+        payload: &crate::models::EnrollDeviceDTO,
+    ) -> Result<crate::models::EnrollDeviceVO, crate::error::AppError> {
+        self.post_data("/api/v1/devices/enroll", payload).await
+    }
+
+    /// Send a verification code to the specified email.  Returns `Ok(())` on
+    /// success; the response `data` is irrelevant for this call.
+    pub async fn send_code(
+        &self,
+        payload: &crate::models::SendCodeDTO,
+    ) -> Result<(), crate::error::AppError> {
+        self.post::<_, serde_json::Value>("/api/v1/auth/send-code", payload)
+            .await
+            .map(|_| ())
+    }
+
+    /// Fetch the logged-in user's profile.
+    pub async fn get_profile(
+        &self,
+        host: &Host,
+    ) -> Result<crate::models::UserProfileVO, crate::error::AppError> {
+        self.auth_get(host, "/api/v1/users/me")
+            .await?
+            .ok_or_else(|| crate::error::AppError::ApiError("No data".to_string()))
+    }
+
+    /// Update editable profile fields and return the refreshed profile.
+    pub async fn update_profile(
+        &self,
+        host: &Host,
+        payload: &crate::models::UpdateProfileDTO,
+    ) -> Result<crate::models::UserProfileVO, crate::error::AppError> {
+        self.auth_put(host, "/api/v1/users/me", payload)
+            .await?
+            .ok_or_else(|| crate::error::AppError::ApiError("No data".to_string()))
+    }
+
+    /// Change the password of the logged-in user.
+    pub async fn change_password(
+        &self,
+        host: &Host,
+        payload: &crate::models::ChangePasswordDTO,
+    ) -> Result<(), crate::error::AppError> {
+        self.auth_post::<_, serde_json::Value>(host, "/api/v1/users/me/password", payload)
+            .await
+            .map(|_| ())
+    }
+
+    /// Whether two-factor authentication is enabled and how many recovery
+    /// codes remain.
+    pub async fn get_2fa_status(
+        &self,
+        host: &Host,
+    ) -> Result<crate::models::TwoFactorStatusVO, crate::error::AppError> {
+        Ok(self.auth_get(host, "/api/v1/users/me/2fa").await?.unwrap_or_default())
+    }
+
+    /// Start TOTP enrollment.  The returned secret is not active until it is
+    /// confirmed with `confirm_totp`.
+    pub async fn enroll_totp(
+        &self,
+        host: &Host,
+    ) -> Result<crate::models::TotpEnrollVO, crate::error::AppError> {
+        self.auth_post(host, "/api/v1/users/me/2fa/totp", &serde_json::json!({}))
+            .await?
+            .ok_or_else(|| crate::error::AppError::ApiError("No data".to_string()))
+    }
+
+    /// Confirm TOTP enrollment with a code from the authenticator app and
+    /// receive the one-time recovery codes.
+    pub async fn confirm_totp(
+        &self,
+        host: &Host,
+        payload: &crate::models::TotpCodeDTO,
+    ) -> Result<crate::models::RecoveryCodesVO, crate::error::AppError> {
+        Self::recovery_codes(self.auth_post(host, "/api/v1/users/me/2fa/totp/confirm", payload).await?)
+    }
+
+    /// The server has already invalidated the previous codes at this point,
+    /// so an empty list must not be shown as success: the user would be left
+    /// without any way to recover the account.
+    fn recovery_codes(
+        data: Option<crate::models::RecoveryCodesVO>,
+    ) -> Result<crate::models::RecoveryCodesVO, crate::error::AppError> {
+        match data {
+            Some(vo) if !vo.codes.is_empty() => Ok(vo),
+            _ => Err(crate::error::AppError::ApiError("No recovery codes returned".to_string())),
+        }
+    }
+
+    /// Turn two-factor authentication off.  Accepts a TOTP or recovery code.
+    pub async fn disable_totp(
+        &self,
+        host: &Host,
+        payload: &crate::models::TotpCodeDTO,
+    ) -> Result<(), crate::error::AppError> {
+        self.auth_post::<_, serde_json::Value>(host, "/api/v1/users/me/2fa/totp/disable", payload)
+            .await
+            .map(|_| ())
+    }
+
+    /// Replace the recovery codes; the previous set stops working.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        host: &Host,
+        payload: &crate::models::TotpCodeDTO,
+    ) -> Result<crate::models::RecoveryCodesVO, crate::error::AppError> {
+        Self::recovery_codes(self.auth_post(host, "/api/v1/users/me/2fa/recovery-codes", payload).await?)
+    }
+
+    /// List the saved payout accounts.
+    pub async fn list_payout_accounts(
+        &self,
+        host: &Host,
+    ) -> Result<Vec<crate::models::PayoutAccountVO>, crate::error::AppError> {
+        Ok(self
+            .auth_get(host, "/api/v1/users/me/payout-accounts")
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Create a payout account, or update it when `account_id` is set.  The
+    /// server re-verifies the email code carried in the payload.
+    pub async fn save_payout_account(
+        &self,
+        host: &Host,
+        payload: &crate::models::PayoutAccountDTO,
+    ) -> Result<crate::models::PayoutAccountVO, crate::error::AppError> {
+        let data = match payload.account_id {
+            Some(id) => {
+                let path = format!("/api/v1/users/me/payout-accounts/{}", id);
+                self.auth_put(host, &path, payload).await?
+            }
+            None => self.auth_post(host, "/api/v1/users/me/payout-accounts", payload).await?,
+        };
+        data.ok_or_else(|| crate::error::AppError::ApiError("No data".to_string()))
+    }
+
+    /// Delete a payout account after email code re-verification.
+    pub async fn delete_payout_account(
+        &self,
+        host: &Host,
+        account_id: i64,
+        payload: &crate::models::PayoutAccountDeleteDTO,
+    ) -> Result<(), crate::error::AppError> {
+        let path = format!("/api/v1/users/me/payout-accounts/{}/delete", account_id);
+        self.auth_post::<_, serde_json::Value>(host, &path, payload)
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, HttpStub, Response};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn login_rejects_business_error() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({ "code": 1001, "data": { "accessToken": "a", "refreshToken": "r" } }))
+        })
+        .await;
+        let (host, _) = testutil::host();
+        assert!(Api::new(srv.base.clone()).login(&host, "u", "p").await.is_err());
+        assert_eq!(Api::access_token(&host).await, None);
+    }
+
+    #[tokio::test]
+    async fn missing_recovery_codes_is_an_error() {
+        for data in [json!(null), json!({}), json!({ "codes": [] })] {
+            let body = json!({ "code": 0, "message": "ok", "data": data });
+            let srv = HttpStub::start(move |_| Response::json(200, body.clone())).await;
+            let (host, _) = testutil::host();
+            let client = ApiClient::with_base(srv.base.clone());
+            let dto = crate::models::TotpCodeDTO { code: "123456".into() };
+            assert!(matches!(client.regenerate_recovery_codes(&host, &dto).await, Err(crate::error::AppError::ApiError(_))));
+            assert!(matches!(client.confirm_totp(&host, &dto).await, Err(crate::error::AppError::ApiError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_recovery_codes() {
+        let srv = HttpStub::start(|_| Response::json(200, json!({ "code": 0, "message": "ok", "data": { "codes": ["a1", "b2"] } }))).await;
+        let (host, _) = testutil::host();
+        let dto = crate::models::TotpCodeDTO { code: "123456".into() };
+        let vo = ApiClient::with_base(srv.base.clone()).regenerate_recovery_codes(&host, &dto).await.unwrap();
+        assert_eq!(vo.codes, ["a1", "b2"]);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_without_refresh_token_reauthenticates() {
+        // 第一次请求 401（token 已过期），重新登录后的重试成功；其余路径一律 401
+        let hits = std::sync::atomic::AtomicUsize::new(0);
+        let srv = HttpStub::start(move |req| {
+            if req.path == "/api/v1/ping" && hits.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0 {
+                Response::json(200, json!({ "code": 0 }))
+            } else {
+                Response::json(401, json!({}))
+            }
+        })
+        .await;
+        let (host, _) = testutil::host();
+        Api::set_auth_token(&host, "expired").await.unwrap();
+        let logins = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (counter, store) = (logins.clone(), host.clone());
+        let api = Api::new(srv.base.clone()).with_reauth(Arc::new(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let host = store.clone();
+            Box::pin(async move { Api::set_auth_token(&host, "fresh").await.map_err(Into::into) })
+        }));
+
+        let _: serde_json::Value = api.auth_get(&host, "/api/v1/ping").await.unwrap();
+        assert_eq!(logins.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(Api::access_token(&host).await.as_deref(), Some("fresh"));
+        assert!(api.refresh_session(&host).await.unwrap());
+        assert_eq!(logins.load(std::sync::atomic::Ordering::SeqCst), 2);
+        // 没有回调时 401 直接返回
+        assert!(Api::new(srv.base.clone()).auth_get::<serde_json::Value>(&host, "/api/v1/other").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn version_thresholds_go_to_the_gate_host() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({ "code": 0, "message": "ok", "data": { "codes": ["a1"] }, "minClientVersion": "999.0.0" }))
+        })
+        .await;
+        let (host, events) = testutil::host();
+        let stops = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = stops.clone();
+        let gate = version::VersionGate::new(host.clone()).on_unsupported(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        let client = ApiClient::with_base(srv.base.clone()).with_version_gate(gate);
+        let dto = crate::models::TotpCodeDTO { code: "123456".into() };
+        client.regenerate_recovery_codes(&host, &dto).await.unwrap();
+        assert_eq!(stops.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(events.named("app:update-required").len(), 1);
+        assert!(version::ensure_supported(&host).is_err());
+
+        // 没有登记门槛去处的 Api 不记录
+        let (other, _) = testutil::host();
+        ApiClient::with_base(srv.base.clone()).regenerate_recovery_codes(&other, &dto).await.unwrap();
+        assert!(version::ensure_supported(&other).is_ok());
+    }
+
+    #[tokio::test]
+    async fn refresh_ignores_business_error() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({ "code": 1001, "data": { "accessToken": "a2", "refreshToken": "r2" } }))
+        })
+        .await;
+        let (host, _) = testutil::host();
+        Api::set_tokens(&host, "a1", "r1").await;
+        assert!(!Api::new(srv.base.clone()).refresh_session(&host).await.unwrap());
+        assert_eq!(Api::access_token(&host).await.as_deref(), Some("a1"));
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::api::Api;
+use crate::error::AppError;
+use crate::host::Host;
+
+// OAuth 2.0 设备授权（RFC 8628）：本机展示 user code / 二维码，用户在手机上确认，
+// 客户端轮询 token 端点直到批准、拒绝或过期。
+
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const CLIENT_ID: &str = "hashtreasure-client";
+// 服务端未给出时使用 RFC 建议的默认值
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const SLOW_DOWN_STEP_SECS: u64 = 5;
+const MAX_INTERVAL_SECS: u64 = 60;
+
+/// 设备授权请求的结果，前端据此展示 user code 与二维码
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAuthorization {
+    #[serde(alias = "device_code")]
+    pub device_code: String,
+    #[serde(alias = "user_code")]
+    pub user_code: String,
+    #[serde(alias = "verification_uri")]
+    pub verification_uri: String,
+    #[serde(default, alias = "verification_uri_complete")]
+    pub verification_uri_complete: Option<String>,
+    #[serde(alias = "expires_in")]
+    pub expires_in: u64,
+    #[serde(default)]
+    pub interval: Option<u64>,
+    /// 二维码内容：优先带 user code 的完整链接，用户扫码后无需再输入
+    #[serde(default)]
+    pub qr_payload: String,
+}
+
+#[derive(Serialize)]
+struct CodeReq<'a> {
+    client_id: &'a str,
+}
+
+#[derive(Serialize)]
+struct TokenReq<'a> {
+    grant_type: &'a str,
+    device_code: &'a str,
+    client_id: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TokenResp {
+    access_token: String,
+    // RFC 8628 / 6749 5.1：refresh_token 可选
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+}
+
+/// 单次轮询的结果
+enum Poll {
+    Approved(TokenResp),
+    Pending,
+    SlowDown,
+}
+
+/// 向授权服务器申请 device code / user code
+pub async fn start(api: &Api) -> Result<DeviceAuthorization, AppError> {
+    let url = format!("{}/api/v1/auth/device/code", api.base);
+    let resp = api.client.post(url).form(&CodeReq { client_id: CLIENT_ID }).send().await?;
+    api.observe_version(resp.headers());
+    if !resp.status().is_success() {
+        return Err(AppError::from_api_code(resp.status().as_u16() as i32, "device authorization failed"));
+    }
+    let mut auth: DeviceAuthorization = resp.json().await?;
+    auth.qr_payload = auth
+        .verification_uri_complete
+        .clone()
+        .unwrap_or_else(|| format!("{}?user_code={}", auth.verification_uri, auth.user_code));
+    Ok(auth)
+}
+
+/// 轮询 token 端点直到用户批准，然后像 Api::set_tokens 一样保存 token。
+/// authorization_pending 按 interval 继续等；slow_down 每次加 5 秒；
+/// 网络错误按指数退避重试；超过 expires_in 或用户拒绝时返回对应错误。
+pub async fn poll_until_approved(api: &Api, host: &Host, auth: &DeviceAuthorization) -> Result<(), AppError> {
+    let deadline = Instant::now() + Duration::from_secs(auth.expires_in);
+    let mut interval = auth.interval.unwrap_or(DEFAULT_INTERVAL_SECS).max(1);
+    let mut backoff = interval;
+    loop {
+        tokio::time::sleep(Duration::from_secs(backoff)).await;
+        if Instant::now() >= deadline {
+            return Err(AppError::AuthorizationExpired);
+        }
+        match poll_once(api, &auth.device_code).await {
+            Ok(Poll::Approved(tokens)) => {
+                match &tokens.refresh_token {
+                    Some(refresh) => Api::set_tokens(host, &tokens.access_token, refresh).await,
+                    None => Api::set_access_token(host, &tokens.access_token).await,
+                }
+                log::info!("device authorization approved");
+                return Ok(());
+            }
+            Ok(Poll::Pending) => backoff = interval,
+            Ok(Poll::SlowDown) => {
+                interval = (interval + SLOW_DOWN_STEP_SECS).min(MAX_INTERVAL_SECS);
+                backoff = interval;
+            }
+            Err(e) if e.retryable() => {
+                log::warn!("device authorization poll failed, retrying: {}", e);
+                backoff = (backoff * 2).min(MAX_INTERVAL_SECS);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn poll_once(api: &Api, device_code: &str) -> Result<Poll, AppError> {
+    let url = format!("{}/api/v1/auth/device/token", api.base);
+    let req = TokenReq { grant_type: GRANT_TYPE, device_code, client_id: CLIENT_ID };
+    let resp = api.client.post(url).form(&req).send().await?;
+    api.observe_version(resp.headers());
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(Poll::Approved(resp.json().await?));
+    }
+    // RFC 8628 3.5：失败时 400 + {"error": "..."}
+    let err = resp.json::<TokenError>().await.map(|e| e.error).unwrap_or_default();
+    match err.as_str() {
+        "authorization_pending" => Ok(Poll::Pending),
+        "slow_down" => Ok(Poll::SlowDown),
+        "expired_token" => Err(AppError::AuthorizationExpired),
+        "access_denied" => Err(AppError::AuthorizationDenied),
+        _ => Err(AppError::from_api_code(status.as_u16() as i32, &err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, HttpStub, Response};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn auth(interval: u64, expires_in: u64) -> DeviceAuthorization {
+        DeviceAuthorization {
+            device_code: "dc-1".into(),
+            user_code: "ABCD-EFGH".into(),
+            verification_uri: "https://example.com/device".into(),
+            verification_uri_complete: None,
+            expires_in,
+            interval: Some(interval),
+            qr_payload: String::new(),
+        }
+    }
+
+    fn token_error(error: &str) -> Response {
+        Response::json(400, json!({ "error": error }))
+    }
+
+    // 依次返回 responses 中的响应，最后一个重复使用
+    async fn token_endpoint(responses: Vec<fn() -> Response>) -> (HttpStub, Arc<AtomicUsize>) {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let srv = HttpStub::start(move |req| {
+            assert_eq!(req.path, "/api/v1/auth/device/token");
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            responses[n.min(responses.len() - 1)]()
+        })
+        .await;
+        (srv, polls)
+    }
+
+    #[tokio::test]
+    async fn start_builds_qr_payload() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({
+                "device_code": "dc-1", "user_code": "ABCD-EFGH",
+                "verification_uri": "https://example.com/device", "expires_in": 600, "interval": 5,
+            }))
+        })
+        .await;
+        let auth = start(&Api::new(srv.base.clone())).await.unwrap();
+        assert_eq!(auth.qr_payload, "https://example.com/device?user_code=ABCD-EFGH");
+        assert_eq!(srv.calls(), ["POST /api/v1/auth/device/code"]);
+    }
+
+    #[tokio::test]
+    async fn approves_after_pending() {
+        let (srv, polls) = token_endpoint(vec![
+            || token_error("authorization_pending"),
+            || Response::json(200, json!({ "access_token": "a1", "refresh_token": "r1", "token_type": "Bearer" })),
+        ])
+        .await;
+        let (host, _) = testutil::host();
+        poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 60)).await.unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        assert_eq!(host.get_str("access_token").as_deref(), Some("a1"));
+        assert_eq!(host.get_str("refresh_token").as_deref(), Some("r1"));
+    }
+
+    #[tokio::test]
+    async fn accepts_token_without_refresh_token() {
+        let (srv, _) = token_endpoint(vec![|| Response::json(200, json!({ "access_token": "a1", "token_type": "Bearer" }))]).await;
+        let (host, _) = testutil::host();
+        host.store().set("refresh_token", json!("stale")).unwrap();
+        poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 60)).await.unwrap();
+        assert_eq!(host.get_str("access_token").as_deref(), Some("a1"));
+        assert_eq!(host.get_str("refresh_token"), None);
+    }
+
+    #[tokio::test]
+    async fn slow_down_increases_interval() {
+        let times = Arc::new(Mutex::new(Vec::new()));
+        let log = times.clone();
+        let srv = HttpStub::start(move |_| {
+            let mut times = log.lock().unwrap();
+            times.push(Instant::now());
+            if times.len() == 1 {
+                token_error("slow_down")
+            } else {
+                Response::json(200, json!({ "access_token": "a1" }))
+            }
+        })
+        .await;
+        let (host, _) = testutil::host();
+        poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 60)).await.unwrap();
+        let times = times.lock().unwrap();
+        // 间隔从 1 秒加到 1 + SLOW_DOWN_STEP_SECS
+        assert!(times[1] - times[0] >= Duration::from_secs(1 + SLOW_DOWN_STEP_SECS));
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_reported() {
+        let (srv, _) = token_endpoint(vec![|| token_error("expired_token")]).await;
+        let (host, _) = testutil::host();
+        let err = poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 60)).await.unwrap_err();
+        assert!(matches!(err, AppError::AuthorizationExpired));
+    }
+
+    #[tokio::test]
+    async fn access_denied_is_reported() {
+        let (srv, _) = token_endpoint(vec![|| token_error("access_denied")]).await;
+        let (host, _) = testutil::host();
+        let err = poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 60)).await.unwrap_err();
+        assert!(matches!(err, AppError::AuthorizationDenied));
+        assert_eq!(host.get_str("access_token"), None);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_expires_in() {
+        let (srv, polls) = token_endpoint(vec![|| token_error("authorization_pending")]).await;
+        let (host, _) = testutil::host();
+        let err = poll_until_approved(&Api::new(srv.base.clone()), &host, &auth(1, 2)).await.unwrap_err();
+        assert!(matches!(err, AppError::AuthorizationExpired));
+        assert!(polls.load(Ordering::SeqCst) >= 1);
+    }
+}
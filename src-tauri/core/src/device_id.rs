@@ -0,0 +1,199 @@
+use sha2::{Digest, Sha256};
+use crate::accounts;
+use crate::error::AppError;
+use crate::host::Host;
+
+const KEY_DEVICE_ID: &str = "device_id";
+const KEY_DEVICE_NAME: &str = "device_name";
+const KEY_LOCAL_FINGERPRINT: &str = "local_fingerprint";
+
+// 指纹加盐，避免直接暴露 machine-id 等原始硬件标识
+const FINGERPRINT_SALT: &str = "HashTreasure/device-fingerprint/v1";
+
+// 桌面端是 windows_subsystem 程序，直接起控制台命令会闪出黑窗口
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn quiet_command(program: &str) -> std::process::Command {
+    #[allow(unused_mut)]
+    let mut cmd = std::process::Command::new(program);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd.stdin(std::process::Stdio::null()).stderr(std::process::Stdio::null());
+    cmd
+}
+
+/// 操作系统级别的机器标识，重装应用不会改变
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .map(|s| s.trim().to_string())
+        .find(|s| !s.is_empty())
+}
+#[cfg(target_os = "windows")]
+fn machine_id() -> Option<String> {
+    let out = quiet_command("reg")
+        .args(["query", r"HKLM\SOFTWARE\Microsoft\Cryptography", "/v", "MachineGuid"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .find(|l| l.contains("MachineGuid"))
+        .and_then(|l| l.split_whitespace().last())
+        .map(|s| s.to_string())
+}
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<String> {
+    let out = quiet_command("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .find(|l| l.contains("IOPlatformUUID"))
+        .and_then(|l| l.split('"').nth(3))
+        .map(|s| s.to_string())
+}
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn machine_id() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_model() -> Option<String> {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()?
+        .lines()
+        .find(|l| l.starts_with("model name"))
+        .and_then(|l| l.split(':').nth(1))
+        .map(|s| s.trim().to_string())
+}
+#[cfg(target_os = "windows")]
+fn cpu_model() -> Option<String> {
+    std::env::var("PROCESSOR_IDENTIFIER").ok()
+}
+#[cfg(target_os = "macos")]
+fn cpu_model() -> Option<String> {
+    let out = quiet_command("sysctl")
+        .args(["-n", "machdep.cpu.brand_string"])
+        .output()
+        .ok()?;
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn cpu_model() -> Option<String> {
+    None
+}
+
+/// 取所有网卡中最小的 MAC，而不是"当前主网卡"，避免切换网卡后指纹变化
+fn stable_mac() -> Option<[u8; 6]> {
+    mac_address::MacAddressIterator::new()
+        .ok()?
+        .map(|m| m.bytes())
+        .filter(|b| b.iter().any(|x| *x != 0))
+        .min()
+}
+
+/// 确定性设备指纹：加盐 SHA-256(machine-id + CPU 型号)；
+/// 只有拿不到 machine-id 时才退回到 MAC，因此网卡变化不会影响大多数机器。
+fn fingerprint(machine_id: Option<&str>, cpu_model: Option<&str>, mac: Option<[u8; 6]>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(FINGERPRINT_SALT);
+    match machine_id {
+        Some(id) => {
+            hasher.update(b"machine-id:");
+            hasher.update(id.as_bytes());
+        }
+        None => {
+            hasher.update(b"mac:");
+            hasher.update(mac.unwrap_or_default());
+        }
+    }
+    hasher.update(b"cpu:");
+    hasher.update(cpu_model.unwrap_or_default().as_bytes());
+    hex::encode(&hasher.finalize()[..16])
+}
+
+fn calc_fingerprint() -> String {
+    let id = machine_id();
+    // 有 machine-id 时用不到 MAC，不必枚举网卡
+    let mac = if id.is_none() { stable_mac() } else { None };
+    fingerprint(id.as_deref(), cpu_model().as_deref(), mac)
+}
+
+/// 本机指纹：已有的沿用（旧版本写入的随机指纹也保留，已注册设备的身份不变），
+/// 没有时才计算确定性指纹并保存
+pub async fn ensure_local_fingerprint(host: &Host) -> Result<String, AppError> {
+    if let Some(fp) = host.get_str(KEY_LOCAL_FINGERPRINT).filter(|fp| !fp.is_empty()) {
+        return Ok(fp);
+    }
+    let fp = calc_fingerprint();
+    host.store().set(KEY_LOCAL_FINGERPRINT, fp.clone().into())?;
+    Ok(fp)
+}
+
+pub async fn get_cloud_device_id(host: &Host) -> Option<String> {
+    host.get_str(&accounts::scoped(host, KEY_DEVICE_ID))
+}
+
+pub async fn get_cloud_device_name(host: &Host) -> Option<String> {
+    host.get_str(KEY_DEVICE_NAME)
+}
+
+/// 记住注册/重命名时使用的设备名，服务端丢失设备时按原名重新注册
+pub async fn set_cloud_device_name(host: &Host, name: &str) {
+    let _ = host.store().set(KEY_DEVICE_NAME, name.into());
+}
+
+/// 写入当前账号名下的云端 deviceId；传 None 表示清除（解绑/重新注册时使用）
+pub async fn set_cloud_device_id(host: &Host, id: Option<&str>) {
+    let _ = match id {
+        Some(id) => host.store().set(&accounts::scoped(host, KEY_DEVICE_ID), id.into()),
+        None => host.store().delete(&accounts::scoped(host, KEY_DEVICE_ID)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use serde_json::json;
+
+    const MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+
+    #[test]
+    fn fingerprint_is_deterministic() {
+        let a = fingerprint(Some("abc"), Some("Ryzen 7"), None);
+        assert_eq!(a, fingerprint(Some("abc"), Some("Ryzen 7"), None));
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, fingerprint(Some("abd"), Some("Ryzen 7"), None));
+        assert_ne!(a, fingerprint(Some("abc"), Some("Ryzen 5"), None));
+        // 加盐：与未加盐的哈希不同
+        assert_ne!(a, hex::encode(&Sha256::digest(b"machine-id:abccpu:Ryzen 7")[..16]));
+    }
+
+    #[test]
+    fn mac_is_only_a_fallback() {
+        // 有 machine-id 时网卡变化不影响指纹
+        assert_eq!(fingerprint(Some("abc"), None, Some(MAC)), fingerprint(Some("abc"), None, None));
+        let by_mac = fingerprint(None, Some("Ryzen 7"), Some(MAC));
+        assert_eq!(by_mac, fingerprint(None, Some("Ryzen 7"), Some(MAC)));
+        assert_ne!(by_mac, fingerprint(None, Some("Ryzen 7"), Some([0x02, 0, 0, 0, 0, 0x02])));
+    }
+
+    #[tokio::test]
+    async fn existing_fingerprint_is_kept() {
+        let (host, _) = testutil::host();
+        host.store().set(KEY_LOCAL_FINGERPRINT, json!("random-from-old-version")).unwrap();
+        assert_eq!(ensure_local_fingerprint(&host).await.unwrap(), "random-from-old-version");
+
+        let (host, _) = testutil::host();
+        let fp = ensure_local_fingerprint(&host).await.unwrap();
+        assert_eq!(fp, calc_fingerprint());
+        assert_eq!(host.get_str(KEY_LOCAL_FINGERPRINT), Some(fp));
+    }
+}
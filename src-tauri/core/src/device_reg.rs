@@ -0,0 +1,364 @@
+use serde::{Deserialize, Serialize};
+use sysinfo::{System, SystemExt};
+use reqwest::StatusCode;
+use crate::api::{Api, ApiClient, HttpStatusError};
+use crate::error::AppError;
+use crate::host::Host;
+use crate::miner::MiningProfile;
+use crate::accounts;
+use crate::models::{EnrollDeviceDTO, EnrollDeviceVO, MiningCredentialsVO};
+use crate::i18n::notify;
+use crate::device_id::{
+    ensure_local_fingerprint, get_cloud_device_id, get_cloud_device_name, set_cloud_device_id,
+    set_cloud_device_name,
+};
+
+/// 服务端"设备不存在"的业务码（HTTP 404 之外的另一种表达）
+pub const CODE_DEVICE_NOT_FOUND: i32 = 40401;
+
+// 通过注册令牌入网的设备：标记位与管理员分配的挖矿档位
+const KEY_ENROLLED: &str = "enrolled";
+const KEY_ASSIGNED_PROFILE: &str = "assigned_profile";
+/// 连续自动重新注册的上限：注册后仍然 404（路径被代理改写等）说明问题不在设备，不再创建新设备
+pub const MAX_REREGISTRATIONS: u32 = 3;
+static RECOVERY: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// 服务端签发的挖矿凭证（随账号切换）
+const KEY_MINING_CREDENTIALS: &str = "mining_credentials";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceRegisterReq<'a> {
+    device_name: &'a str,
+    device_type: &'a str,
+    device_info: serde_json::Value,
+}
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceRenameReq<'a> {
+    device_name: &'a str,
+}
+#[derive(Deserialize)]
+struct ApiResp<T> { code: i32, data: Option<T> }
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceVo {
+    pub device_id: String,
+    pub device_name: String,
+    #[serde(default)]
+    pub device_type: Option<String>,
+    #[serde(default)]
+    pub online: bool,
+    #[serde(default)]
+    pub last_heartbeat: Option<String>,
+    // 由客户端填充：是否为本机
+    #[serde(default)]
+    pub current: bool,
+}
+
+fn host_name() -> String {
+    let mut sys = System::new();
+    sys.refresh_system();
+    sys.host_name().unwrap_or_else(|| "MyPC".into())
+}
+
+// 默认设备名 "用户名@主机名"；未给出用户名时取当前账号，都没有（如令牌入网的设备）时只用主机名
+fn default_device_name(host: &Host, username: Option<&str>) -> String {
+    let user = username
+        .map(|u| u.to_string())
+        .or_else(|| accounts::active(host).map(|a| a.name.unwrap_or(a.id)));
+    match user {
+        Some(u) => format!("{}@{}", u, host_name()),
+        None => host_name(),
+    }
+}
+
+fn device_info(fingerprint: &str) -> serde_json::Value {
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    serde_json::json!({
+        "fingerprint": fingerprint,
+        "os": sys.name(),
+        "kernel_version": sys.kernel_version(),
+        "cpu_count": sys.cpus().len(),
+        "total_memory": sys.total_memory(),
+    })
+}
+
+/// 确保本机已在云端注册并返回 deviceId，同时刷新本设备的挖矿凭证
+pub async fn ensure_registered(api: &Api, host: &Host, username: &str) -> anyhow::Result<String> {
+    let id = ensure_device(api, host, username).await?;
+    refresh_mining_credentials(api, host, &id).await;
+    Ok(id)
+}
+
+async fn ensure_device(api: &Api, host: &Host, username: &str) -> anyhow::Result<String> {
+    if let Some(id) = get_cloud_device_id(host).await {
+        // 校验缓存的 deviceId 仍然有效；离线等其它错误时沿用缓存
+        let path = format!("/api/v1/devices/{}", id);
+        let res: anyhow::Result<ApiResp<DeviceVo>> = api.auth_get(host, &path).await;
+        match res {
+            Ok(resp) if resp.code == CODE_DEVICE_NOT_FOUND => {}
+            Err(e) if is_unknown_device(&e) => {}
+            _ => return Ok(id),
+        }
+        log::warn!("cached device {} is unknown to the server, registering again", id);
+        set_cloud_device_id(host, None).await;
+    }
+    let name = match get_cloud_device_name(host).await {
+        Some(n) => n,
+        None => default_device_name(host, Some(username)),
+    };
+    register_device(api, host, &name).await
+}
+
+/// 判断错误是否表示服务端已不认识本设备
+pub fn is_unknown_device(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<HttpStatusError>()
+        .map(|e| e.status == StatusCode::NOT_FOUND)
+        .unwrap_or(false)
+}
+
+/// 服务端丢失设备时的自动恢复：清掉缓存的 deviceId，用同一指纹和原设备名重新注册。
+/// 返回新的 deviceId，并通知前端。`stale_id` 是调用方收到 404 时使用的 id：
+/// 心跳与远程通道可能同时遇到 404，恢复过程串行执行，store 中的 id 已被另一方换掉时直接沿用，
+/// 避免注册出两台设备。
+pub async fn recover_unknown_device(api: &Api, host: &Host, stale_id: &str) -> anyhow::Result<String> {
+    let _guard = RECOVERY.lock().await;
+    if let Some(id) = get_cloud_device_id(host).await.filter(|id| id != stale_id) {
+        return Ok(id);
+    }
+    set_cloud_device_id(host, None).await;
+    let name = match get_cloud_device_name(host).await {
+        Some(n) => n,
+        None => default_device_name(host, None),
+    };
+    let id = register_device(api, host, &name).await?;
+    log::info!("device re-registered as {}", id);
+    refresh_mining_credentials(api, host, &id).await;
+    notify(host, "device:reregistered", "noticeDeviceReregistered", serde_json::json!({ "deviceId": id }));
+    Ok(id)
+}
+
+/// 以给定名称向服务端注册本机（相同指纹），并把云端 deviceId 写入 store
+pub async fn register_device(api: &Api, host: &Host, name: &str) -> anyhow::Result<String> {
+    let finger = ensure_local_fingerprint(host).await?;
+    let req = DeviceRegisterReq { device_name: name, device_type: "PC", device_info: device_info(&finger) };
+    let resp: ApiResp<DeviceVo> = api.auth_post(host, "/api/v1/devices", &req).await?;
+    let dev = resp.data.ok_or_else(|| anyhow::anyhow!("empty device data (code {})", resp.code))?;
+    set_cloud_device_id(host, Some(&dev.device_id)).await;
+    set_cloud_device_name(host, &dev.device_name).await;
+    Ok(dev.device_id)
+}
+
+/// 列出当前账号下的全部设备（含在线状态与最近心跳时间）
+pub async fn list_devices(api: &Api, host: &Host) -> anyhow::Result<Vec<DeviceVo>> {
+    let resp: ApiResp<Vec<DeviceVo>> = api.auth_get(host, "/api/v1/devices").await?;
+    if resp.code != 0 { anyhow::bail!("list devices failed: code {}", resp.code); }
+    let current = get_cloud_device_id(host).await;
+    let mut devices = resp.data.unwrap_or_default();
+    for d in devices.iter_mut() {
+        d.current = current.as_deref() == Some(d.device_id.as_str());
+    }
+    Ok(devices)
+}
+
+/// 重命名本机
+pub async fn rename_current_device(api: &Api, host: &Host, name: &str) -> anyhow::Result<()> {
+    let id = get_cloud_device_id(host)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("device not registered"))?;
+    let path = format!("/api/v1/devices/{}", id);
+    let resp: ApiResp<serde_json::Value> = api.auth_put(host, &path, &DeviceRenameReq { device_name: name }).await?;
+    if resp.code != 0 { anyhow::bail!("rename device failed: code {}", resp.code); }
+    set_cloud_device_name(host, name).await;
+    Ok(())
+}
+
+/// 远程解绑任意设备；若解绑的是本机，同时清掉本地的注册信息（deviceId 与挖矿凭证）。
+/// 返回是否解绑了本机，调用方据此停止挖矿
+pub async fn unbind_device(api: &Api, host: &Host, device_id: &str) -> anyhow::Result<bool> {
+    let path = format!("/api/v1/devices/{}", device_id);
+    let resp: ApiResp<serde_json::Value> = api.auth_delete(host, &path).await?;
+    if resp.code != 0 { anyhow::bail!("unbind device failed: code {}", resp.code); }
+    if get_cloud_device_id(host).await.as_deref() != Some(device_id) {
+        return Ok(false);
+    }
+    set_cloud_device_id(host, None).await;
+    host.store().delete(&accounts::scoped(host, KEY_MINING_CREDENTIALS))?;
+    Ok(true)
+}
+
+/// 忽略本地缓存，重新注册本机；注册成功后才替换缓存的 deviceId，失败时保留原设备
+pub async fn reregister_device(api: &Api, host: &Host, name: Option<&str>) -> anyhow::Result<String> {
+    let name = match name {
+        Some(n) => n.to_string(),
+        None => default_device_name(host, None),
+    };
+    register_device(api, host, &name).await
+}
+
+/// 拉取服务端为本设备签发的挖矿凭证并缓存。服务端未启用（404 或空数据）时清除缓存，
+/// 回落到内置钱包；网络等其它错误沿用上次的缓存。
+pub async fn refresh_mining_credentials(api: &Api, host: &Host, device_id: &str) {
+    let key = accounts::scoped(host, KEY_MINING_CREDENTIALS);
+    let path = format!("/api/v1/devices/{}/mining-credentials", device_id);
+    let res: anyhow::Result<ApiResp<MiningCredentialsVO>> = api.auth_get(host, &path).await;
+    let saved = match res {
+        Ok(ApiResp { code: 0, data: Some(c) }) => {
+            serde_json::to_value(&c).map_err(AppError::from).and_then(|v| host.store().set(&key, v))
+        }
+        Ok(_) => host.store().delete(&key),
+        Err(e) if is_unknown_device(&e) => host.store().delete(&key),
+        Err(e) => {
+            log::warn!("fetching mining credentials failed, keeping cached: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = saved {
+        log::warn!("saving mining credentials failed: {}", e);
+    }
+}
+
+/// 缓存的挖矿凭证（当前账号）
+pub fn mining_credentials(host: &Host) -> Option<MiningCredentialsVO> {
+    host.store()
+        .get(&accounts::scoped(host, KEY_MINING_CREDENTIALS))
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// 是否已用注册令牌换过设备凭证（令牌通常一次性，换过就不再重复提交）
+pub fn is_enrolled(host: &Host) -> bool {
+    host.store().get(KEY_ENROLLED).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// 管理员为本机分配的挖矿档位，优先于本地配置
+pub fn assigned_profile(host: &Host) -> Option<MiningProfile> {
+    host.store()
+        .get(KEY_ASSIGNED_PROFILE)
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// 批量部署：用注册令牌换取设备级 access/refresh token，并记下 deviceId 与分配的档位。
+/// 之后的 ensure_registered / 心跳都以设备身份调用，无需用户账号。
+pub async fn enroll(
+    client: &ApiClient,
+    host: &Host,
+    enrollment_token: &str,
+    name: Option<&str>,
+) -> Result<EnrollDeviceVO, AppError> {
+    let fingerprint = ensure_local_fingerprint(host).await?;
+    let payload = EnrollDeviceDTO {
+        enrollment_token: enrollment_token.trim().to_string(),
+        device_info: device_info(&fingerprint),
+        fingerprint,
+        device_name: name.map(|n| n.to_string()).unwrap_or_else(host_name),
+    };
+    payload.validate()?;
+    log::info!("enrolling device: {:?}", payload);
+    let vo = client.enroll_device(&payload).await?;
+    Api::set_tokens(host, &vo.access_token, &vo.refresh_token).await;
+    set_cloud_device_id(host, Some(&vo.device_id)).await;
+    set_cloud_device_name(host, &vo.device_name).await;
+    match vo.profile {
+        Some(p) => host.store().set(KEY_ASSIGNED_PROFILE, serde_json::to_value(p)?)?,
+        None => host.store().delete(KEY_ASSIGNED_PROFILE)?,
+    }
+    host.store().set(KEY_ENROLLED, true.into())?;
+    log::info!("enrolled as {:?}", vo);
+    Ok(vo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, HttpStub, Response};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn unbinding_this_device_clears_local_registration() {
+        let srv = HttpStub::start(|_| Response::json(200, json!({ "code": 0 }))).await;
+        let (host, _) = testutil::host();
+        set_cloud_device_id(&host, Some("dev-1")).await;
+        host.store().set("mining_credentials", json!({ "user": "u1" })).unwrap();
+        let api = Api::new(srv.base.clone());
+
+        assert!(!unbind_device(&api, &host, "dev-2").await.unwrap());
+        assert_eq!(get_cloud_device_id(&host).await.as_deref(), Some("dev-1"));
+        assert!(unbind_device(&api, &host, "dev-1").await.unwrap());
+        assert!(get_cloud_device_id(&host).await.is_none());
+        assert!(mining_credentials(&host).is_none());
+        assert_eq!(srv.calls(), ["DELETE /api/v1/devices/dev-2", "DELETE /api/v1/devices/dev-1"]);
+    }
+
+    #[tokio::test]
+    async fn failed_reregister_keeps_device_id() {
+        let srv = HttpStub::start(|_| Response::json(500, json!({}))).await;
+        let (host, _) = testutil::host();
+        set_cloud_device_id(&host, Some("dev-1")).await;
+        let api = Api::new(srv.base.clone());
+
+        assert!(reregister_device(&api, &host, Some("pc")).await.is_err());
+        assert_eq!(get_cloud_device_id(&host).await.as_deref(), Some("dev-1"));
+        assert_eq!(srv.calls(), ["POST /api/v1/devices"]);
+    }
+
+    #[tokio::test]
+    async fn reregister_replaces_device_id_and_uses_account_name() {
+        let srv = HttpStub::start(|req| {
+            let name = req.json()["deviceName"].clone();
+            Response::json(200, json!({ "code": 0, "data": { "deviceId": "dev-2", "deviceName": name } }))
+        })
+        .await;
+        let (host, _) = testutil::host();
+        set_cloud_device_id(&host, Some("dev-1")).await;
+        accounts::activate(&host, "u1", Some("alice"), None).unwrap();
+        let api = Api::new(srv.base.clone());
+
+        assert_eq!(reregister_device(&api, &host, None).await.unwrap(), "dev-2");
+        assert_eq!(get_cloud_device_id(&host).await.as_deref(), Some("dev-2"));
+        assert_eq!(get_cloud_device_name(&host).await.unwrap(), format!("alice@{}", host_name()));
+    }
+
+    #[tokio::test]
+    async fn concurrent_recovery_registers_once() {
+        let srv = HttpStub::start(|req| match req.method.as_str() {
+            "POST" => Response::json(200, json!({ "code": 0, "data": { "deviceId": "dev-2", "deviceName": "pc" } })),
+            _ => Response::json(404, json!({})),
+        })
+        .await;
+        let (host, _) = testutil::host();
+        set_cloud_device_id(&host, Some("dev-1")).await;
+        let api = Api::new(srv.base.clone());
+
+        // 心跳与远程通道同时拿着旧 id 遇到 404
+        let (a, b) = tokio::join!(recover_unknown_device(&api, &host, "dev-1"), recover_unknown_device(&api, &host, "dev-1"));
+        assert_eq!((a.unwrap(), b.unwrap()), ("dev-2".to_string(), "dev-2".to_string()));
+        assert_eq!(srv.calls().iter().filter(|c| *c == "POST /api/v1/devices").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn list_devices_surfaces_server_errors() {
+        let srv = HttpStub::start(|_| Response::json(200, json!({ "code": 50000, "data": null }))).await;
+        let (host, _) = testutil::host();
+        let api = Api::new(srv.base.clone());
+        assert!(list_devices(&api, &host).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn list_devices_marks_current() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({ "code": 0, "data": [
+                { "deviceId": "dev-1", "deviceName": "a", "online": true },
+                { "deviceId": "dev-2", "deviceName": "b" },
+            ] }))
+        })
+        .await;
+        let (host, _) = testutil::host();
+        set_cloud_device_id(&host, Some("dev-2")).await;
+        let api = Api::new(srv.base.clone());
+        let devices = list_devices(&api, &host).await.unwrap();
+        assert_eq!(devices.iter().map(|d| d.current).collect::<Vec<_>>(), [false, true]);
+        assert_eq!(serde_json::to_value(&devices[0]).unwrap()["deviceId"], "dev-1");
+    }
+}
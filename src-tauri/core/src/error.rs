@@ -0,0 +1,318 @@
+use crate::i18n::tr;
+use serde::{ser::SerializeStruct, ser::Serializer, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// Display 按当前语言渲染，key 与前端 locales 保持一致
+fn msg(key: &str) -> String {
+    tr(key, &json!({}))
+}
+fn msg_detail(key: &str, detail: &dyn std::fmt::Display) -> String {
+    tr(key, &json!({ "detail": detail.to_string() }))
+}
+fn msg_field(key: &str, field: &str) -> String {
+    tr(key, &json!({ "field": field }))
+}
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{}", msg("errorNetwork"))]
+    NetworkError(#[source] reqwest::Error),
+
+    #[error("{}", msg("errorJsonParse"))]
+    JsonParseError(#[source] BoxError),
+
+    #[error("{}", msg_detail("errorApi", .0))]
+    ApiError(String),
+
+    #[error("{}", msg("errorPath"))]
+    PathError,
+
+    // 本地存储（桌面端 store 插件 / daemon 的 JSON 文件）读写失败
+    #[error("{}", msg_detail("errorStore", .0))]
+    StoreError(String),
+
+    #[error("{}", msg("errorIo"))]
+    IoError(#[from] std::io::Error),
+
+    #[error("{}", msg("errorInvalidCredentials"))]
+    InvalidCredentials,
+
+    // 已登录状态下的请求被拒（token 过期/被吊销），与登录时的密码错误区分
+    #[error("{}", msg("errorSessionExpired"))]
+    SessionExpired,
+
+    #[error("{}", msg("errorWeakPassword"))]
+    WeakPassword,
+
+    #[error("{}", msg_field("errorInvalidInput", .0))]
+    InvalidInput(&'static str),
+
+    #[error("{}", msg("errorRateLimited"))]
+    RateLimited,
+
+    #[error("{}", msg("errorEmailAlreadyRegistered"))]
+    EmailAlreadyRegistered,
+
+    #[error("{}", msg("errorInvalidVerificationCode"))]
+    InvalidVerificationCode,
+
+    #[error("{}", msg("errorAccountLocked"))]
+    AccountLocked,
+
+    #[error("{}", msg("errorServer"))]
+    ServerError,
+
+    #[error("{}", msg("errorTwoFactorRequired"))]
+    TwoFactorRequired(String),
+
+    #[error("{}", msg("errorAuthorizationExpired"))]
+    AuthorizationExpired,
+
+    #[error("{}", msg("errorAuthorizationDenied"))]
+    AuthorizationDenied,
+
+    #[error("{}", msg("errorMiningAlreadyRunning"))]
+    MiningAlreadyRunning,
+
+    #[error("{}", msg("errorMiningNotRunning"))]
+    MiningNotRunning,
+
+    #[error("{}", msg("errorDeviceNotRegistered"))]
+    DeviceNotRegistered,
+
+    #[error("{}", msg("errorUpdateRequired"))]
+    UpdateRequired(String),
+
+    #[error("{}", msg_detail("errorUpdateFailed", .0))]
+    UpdateFailed(String),
+
+    #[error("{}", msg_detail("errorMining", .0))]
+    MiningError(String),
+
+    #[error("{}", msg("errorPoolUserMissing"))]
+    PoolUserMissing,
+
+    #[error("{}", msg("errorMinerApiUnavailable"))]
+    MinerApiUnavailable,
+
+    #[error("{}", msg("errorUnknown"))]
+    Unknown,
+}
+
+// reqwest 的解码错误归为 JSON 解析失败，其余归为网络错误，两者都保留原始 cause
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            AppError::JsonParseError(Box::new(e))
+        } else {
+            AppError::NetworkError(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::JsonParseError(Box::new(e))
+    }
+}
+
+// anyhow 仅用于 Api 内部辅助函数，到命令边界时尽量还原成具体变体
+impl From<anyhow::Error> for AppError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(status) = e.downcast_ref::<crate::api::HttpStatusError>() {
+            return AppError::from_auth_code(status.status.as_u16() as i32, &status.to_string());
+        }
+        match e.downcast::<reqwest::Error>() {
+            Ok(re) => re.into(),
+            Err(e) => AppError::ApiError(format!("{:#}", e)),
+        }
+    }
+}
+
+impl AppError {
+    /// 前端据此分支处理错误。这些字符串是对外契约，只能新增，不要修改已有值。
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NetworkError(_) => "NETWORK",
+            AppError::JsonParseError(_) => "JSON_PARSE",
+            AppError::ApiError(_) => "API",
+            AppError::PathError => "PATH",
+            AppError::StoreError(_) => "STORE",
+            AppError::IoError(_) => "IO",
+            AppError::InvalidCredentials => "INVALID_CREDENTIALS",
+            AppError::SessionExpired => "SESSION_EXPIRED",
+            AppError::WeakPassword => "WEAK_PASSWORD",
+            AppError::InvalidInput(_) => "INVALID_INPUT",
+            AppError::RateLimited => "RATE_LIMITED",
+            AppError::EmailAlreadyRegistered => "EMAIL_ALREADY_REGISTERED",
+            AppError::InvalidVerificationCode => "INVALID_VERIFICATION_CODE",
+            AppError::AccountLocked => "ACCOUNT_LOCKED",
+            AppError::ServerError => "SERVER_ERROR",
+            AppError::TwoFactorRequired(_) => "TWO_FACTOR_REQUIRED",
+            AppError::AuthorizationExpired => "AUTHORIZATION_EXPIRED",
+            AppError::AuthorizationDenied => "AUTHORIZATION_DENIED",
+            AppError::MiningAlreadyRunning => "MINING_ALREADY_RUNNING",
+            AppError::MiningNotRunning => "MINING_NOT_RUNNING",
+            AppError::DeviceNotRegistered => "DEVICE_NOT_REGISTERED",
+            AppError::UpdateRequired(_) => "UPDATE_REQUIRED",
+            AppError::UpdateFailed(_) => "UPDATE_FAILED",
+            AppError::MiningError(_) => "MINING",
+            AppError::PoolUserMissing => "POOL_USER_MISSING",
+            AppError::MinerApiUnavailable => "MINER_API_UNAVAILABLE",
+            AppError::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// 与前端 locales 中一致的翻译 key
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            AppError::NetworkError(_) => "errorNetwork",
+            AppError::JsonParseError(_) => "errorJsonParse",
+            AppError::ApiError(_) => "errorApi",
+            AppError::PathError => "errorPath",
+            AppError::StoreError(_) => "errorStore",
+            AppError::IoError(_) => "errorIo",
+            AppError::InvalidCredentials => "errorInvalidCredentials",
+            AppError::SessionExpired => "errorSessionExpired",
+            AppError::WeakPassword => "errorWeakPassword",
+            AppError::InvalidInput(_) => "errorInvalidInput",
+            AppError::RateLimited => "errorRateLimited",
+            AppError::EmailAlreadyRegistered => "errorEmailAlreadyRegistered",
+            AppError::InvalidVerificationCode => "errorInvalidVerificationCode",
+            AppError::AccountLocked => "errorAccountLocked",
+            AppError::ServerError => "errorServer",
+            AppError::TwoFactorRequired(_) => "errorTwoFactorRequired",
+            AppError::AuthorizationExpired => "errorAuthorizationExpired",
+            AppError::AuthorizationDenied => "errorAuthorizationDenied",
+            AppError::MiningAlreadyRunning => "errorMiningAlreadyRunning",
+            AppError::MiningNotRunning => "errorMiningNotRunning",
+            AppError::DeviceNotRegistered => "errorDeviceNotRegistered",
+            AppError::UpdateRequired(_) => "errorUpdateRequired",
+            AppError::UpdateFailed(_) => "errorUpdateFailed",
+            AppError::MiningError(_) => "errorMining",
+            AppError::PoolUserMissing => "errorPoolUserMissing",
+            AppError::MinerApiUnavailable => "errorMinerApiUnavailable",
+            AppError::Unknown => "errorUnknown",
+        }
+    }
+
+    /// 翻译模板的插值参数
+    pub fn params(&self) -> Value {
+        match self {
+            AppError::ApiError(d) | AppError::MiningError(d) | AppError::StoreError(d) | AppError::UpdateFailed(d) => {
+                json!({ "detail": d })
+            }
+            AppError::InvalidInput(field) => json!({ "field": field }),
+            AppError::TwoFactorRequired(challenge_id) => json!({ "challengeId": challenge_id }),
+            AppError::UpdateRequired(minimum) => json!({ "minimum": minimum }),
+            _ => json!({}),
+        }
+    }
+
+    /// 同样的请求稍后重试是否可能成功
+    pub fn retryable(&self) -> bool {
+        matches!(self, AppError::NetworkError(_) | AppError::RateLimited | AppError::ServerError)
+    }
+
+    /// 底层 cause 链，便于排查；没有 source 时为 None
+    pub fn details(&self) -> Option<String> {
+        let mut source = std::error::Error::source(self);
+        let mut parts = Vec::new();
+        while let Some(e) = source {
+            parts.push(e.to_string());
+            source = e.source();
+        }
+        if parts.is_empty() { None } else { Some(parts.join(": ")) }
+    }
+}
+
+// 实现 Serialize trait，以便能将错误传递给前端：
+// { code, key, params, message, details, retryable }
+impl Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("AppError", 6)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("key", self.message_key())?;
+        s.serialize_field("params", &self.params())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("details", &self.details())?;
+        s.serialize_field("retryable", &self.retryable())?;
+        s.end()
+    }
+}
+
+// 服务端业务码对照表（ApiResponse.code，0 表示成功）：
+//
+// | code        | 含义                 | AppError                |
+// |-------------|----------------------|-------------------------|
+// | 401/403/1001| 用户名或密码错误     | InvalidCredentials      |
+// | 1002        | 账号已锁定           | AccountLocked           |
+// | 1003        | 验证码错误或已过期   | InvalidVerificationCode |
+// | 1004        | 邮箱已注册           | EmailAlreadyRegistered  |
+// | 429/1429    | 请求过于频繁         | RateLimited             |
+// | 500..=599   | 服务端内部错误       | ServerError             |
+// | 其它        | 未知                 | ApiError(message)       |
+//
+// 带 token 的请求改用 from_auth_code：HTTP 401/403 表示会话失效（SessionExpired）。
+impl AppError {
+    pub fn from_api_code(code: i32, message: &str) -> Self {
+        match code {
+            401 | 403 | 1001 => AppError::InvalidCredentials,
+            1002 => AppError::AccountLocked,
+            1003 => AppError::InvalidVerificationCode,
+            1004 => AppError::EmailAlreadyRegistered,
+            429 | 1429 => AppError::RateLimited,
+            500..=599 => AppError::ServerError,
+            _ => AppError::ApiError(format!("code {}: {}", code, message)),
+        }
+    }
+
+    /// 带 token 的请求：401/403 是会话失效，而不是密码错误
+    pub fn from_auth_code(code: i32, message: &str) -> Self {
+        match code {
+            401 | 403 => AppError::SessionExpired,
+            _ => AppError::from_api_code(code, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::HttpStatusError;
+    use reqwest::{Method, StatusCode};
+
+    fn from_status(status: StatusCode) -> AppError {
+        anyhow::Error::from(HttpStatusError { method: Method::GET, status }).into()
+    }
+
+    #[test]
+    fn authenticated_401_is_session_expired() {
+        assert!(matches!(from_status(StatusCode::UNAUTHORIZED), AppError::SessionExpired));
+        assert!(matches!(from_status(StatusCode::FORBIDDEN), AppError::SessionExpired));
+        assert!(matches!(AppError::from_api_code(401, ""), AppError::InvalidCredentials));
+        assert!(matches!(AppError::from_auth_code(1001, ""), AppError::InvalidCredentials));
+    }
+
+    #[test]
+    fn anyhow_keeps_retryable() {
+        let e = from_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(matches!(e, AppError::ServerError) && e.retryable());
+        let e: AppError = anyhow::anyhow!("boom").into();
+        assert!(matches!(e, AppError::ApiError(_)) && !e.retryable());
+    }
+
+    #[test]
+    fn serializes_code_and_key() {
+        let v = serde_json::to_value(AppError::SessionExpired).unwrap();
+        assert_eq!(v["code"], "SESSION_EXPIRED");
+        assert_eq!(v["key"], "errorSessionExpired");
+        assert_eq!(v["retryable"], false);
+    }
+}
@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use sysinfo::{CpuExt, System, SystemExt};
+use crate::api::Api;
+use crate::error::AppError;
+use crate::host::Host;
+use crate::miner::{MiningManager, MiningProfile};
+use crate::i18n::{notify, notify_error};
+use crate::remote_config;
+use crate::version;
+use crate::device_id::get_cloud_device_id;
+use crate::device_reg::{is_unknown_device, recover_unknown_device, CODE_DEVICE_NOT_FOUND, MAX_REREGISTRATIONS};
+use tokio::time::Duration;
+
+// 默认心跳周期与退避上限（服务端可通过响应覆盖心跳周期）
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+pub(crate) const MIN_INTERVAL_SECS: u64 = 5;
+pub(crate) const MAX_INTERVAL_SECS: u64 = 3600;
+const MAX_BACKOFF_SECS: u64 = 480;
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HeartbeatPayload {
+    pub cpu_usage: String,
+    pub gpu_usage: String,
+    pub memory_usage: f64,
+    pub cpu_hashrate: f64,
+    pub gpu_hashrate: f64,
+}
+
+/// 服务端下发给客户端的控制指令
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HeartbeatCommand {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+    SwitchProfile { profile: MiningProfile },
+    Update { version: String, url: Option<String> },
+    // 旧客户端不认识的指令直接忽略，保持向前兼容
+    #[serde(other)]
+    Unknown,
+}
+
+/// 心跳响应中的 data 部分
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HeartbeatVO {
+    pub next_interval_sec: Option<u64>,
+    #[serde(default)]
+    pub commands: Vec<HeartbeatCommand>,
+}
+
+#[derive(Deserialize)]
+struct HeartbeatResp {
+    code: i32,
+    message: Option<String>,
+    data: Option<HeartbeatVO>,
+}
+
+/// 心跳采样：真实的 CPU / 内存占用 + XMRig 最近一次 10s 算力（桌面端与 daemon 共用）
+pub fn sampler(miner: Arc<MiningManager>) -> impl FnMut() -> HeartbeatPayload + Send + 'static {
+    let mut sys = System::new();
+    move || {
+        sys.refresh_cpu();
+        sys.refresh_memory();
+        let memory = match sys.total_memory() {
+            0 => 0.0,
+            total => sys.used_memory() as f64 * 100.0 / total as f64,
+        };
+        HeartbeatPayload {
+            cpu_usage: format!("{:.1}", sys.global_cpu_info().cpu_usage()),
+            gpu_usage: "0".into(),
+            memory_usage: memory,
+            cpu_hashrate: miner.hashrate_now().unwrap_or(0.0),
+            gpu_hashrate: 0.0,
+        }
+    }
+}
+
+pub async fn spawn_heartbeat(
+    api: Api,
+    host: Host,
+    miner: Arc<MiningManager>,
+    mut device_id: String,
+    mut sampler: impl FnMut() -> HeartbeatPayload + Send + 'static,
+) {
+    tokio::spawn(async move {
+        let mut backoff = 1u64;
+        // 连续自动重新注册次数，心跳成功后清零
+        let mut recoveries = 0u32;
+        // 最近一次心跳响应指定的周期；响应未带该字段时回到远程配置
+        let mut server_period: Option<u64> = None;
+        loop {
+            let period = interval(&host, server_period);
+            // 切换账号后设备绑定随之变化，每轮以 store 中当前账号的 deviceId 为准
+            if let Some(id) = get_cloud_device_id(&host).await {
+                device_id = id;
+            }
+            let payload = sampler();
+            let path = format!("/api/v1/devices/{}/heartbeat", device_id);
+            let res: anyhow::Result<HeartbeatResp> = api.auth_post(&host, &path, &payload).await;
+            match res {
+                Ok(resp) if resp.code == 0 => {
+                    backoff = 1;
+                    recoveries = 0;
+                    host.emit("heartbeat:ok", &payload);
+                    let vo = resp.data.unwrap_or_default();
+                    server_period = vo.next_interval_sec;
+                    for cmd in vo.commands {
+                        let _ = apply_command(&host, &miner, cmd).await;
+                    }
+                    // 服务端把最低版本提到当前版本之上：停止挖矿，等待升级
+                    if let Err(e) = version::ensure_supported(&host) {
+                        if miner.is_running().await {
+                            log::warn!("stopping miner: {}", e);
+                            let _ = miner.stop().await;
+                        }
+                    }
+                    tokio::time::sleep(Duration::from_secs(interval(&host, server_period))).await;
+                }
+                // 服务端已删除本设备：自动重新注册后继续心跳
+                Ok(HeartbeatResp { code: CODE_DEVICE_NOT_FOUND, .. }) => {
+                    device_id = recover_or_wait(&api, &host, device_id, &mut recoveries, &mut backoff).await;
+                }
+                Err(err) if is_unknown_device(&err) => {
+                    device_id = recover_or_wait(&api, &host, device_id, &mut recoveries, &mut backoff).await;
+                }
+                other => {
+                    let err = match other {
+                        Ok(resp) => AppError::from_auth_code(resp.code, &resp.message.unwrap_or_default()),
+                        Err(err) => AppError::from(err),
+                    };
+                    notify_error(&host, "heartbeat:err", &err);
+                    let wait = backoff.max(period).min(MAX_BACKOFF_SECS);
+                    tokio::time::sleep(Duration::from_secs(wait)).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+                }
+            }
+        }
+    });
+}
+
+// 周期优先级：心跳响应指定（限制在 MIN..=MAX 内）> 远程配置 > 默认值
+fn interval(host: &Host, server_period: Option<u64>) -> u64 {
+    server_period
+        .map(|secs| secs.clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS))
+        .or_else(|| remote_config::current(host).and_then(|c| c.heartbeat_interval_sec))
+        .unwrap_or(DEFAULT_INTERVAL_SECS)
+}
+
+// 重新注册后同样退避，持续 404 时不会变成注册请求的死循环；连续次数达到上限后只退避重试
+async fn recover_or_wait(api: &Api, host: &Host, device_id: String, recoveries: &mut u32, backoff: &mut u64) -> String {
+    let next = if *recoveries >= MAX_REREGISTRATIONS {
+        log::warn!("device {} still unknown after {} re-registrations", device_id, recoveries);
+        notify_error(host, "heartbeat:err", &AppError::ApiError(format!("device {} not found", device_id)));
+        device_id
+    } else {
+        *recoveries += 1;
+        match recover_unknown_device(api, host, &device_id).await {
+            Ok(id) => id,
+            Err(e) => {
+                notify_error(host, "heartbeat:err", &AppError::from(e));
+                device_id
+            }
+        }
+    };
+    tokio::time::sleep(Duration::from_secs(*backoff)).await;
+    *backoff = (*backoff * 2).min(MAX_BACKOFF_SECS);
+    next
+}
+
+/// 通过 MiningManager 执行服务端下发的指令，并把结果广播给前端。
+/// 心跳与 WebSocket 远程通道共用此入口。
+pub(crate) async fn apply_command(host: &Host, manager: &MiningManager, cmd: HeartbeatCommand) -> Result<(), AppError> {
+    let result = match &cmd {
+        HeartbeatCommand::Start => {
+            if manager.is_running().await { Ok(()) } else { manager.start(host.clone()).await }
+        }
+        HeartbeatCommand::Stop => {
+            if manager.is_running().await { manager.stop().await } else { Ok(()) }
+        }
+        HeartbeatCommand::Pause => manager.pause().await,
+        HeartbeatCommand::Resume => manager.resume().await,
+        HeartbeatCommand::SwitchProfile { profile } => manager.set_profile(host.clone(), *profile).await,
+        HeartbeatCommand::Update { .. } => {
+            // 升级由前端提示用户完成，这里只负责转发
+            host.emit("heartbeat:update", &cmd);
+            Ok(())
+        }
+        HeartbeatCommand::Unknown => return Err(AppError::ApiError("unknown command".into())),
+    };
+    match &result {
+        Ok(()) => {
+            let name = serde_json::to_value(&cmd).ok().and_then(|v| v["type"].as_str().map(|s| s.to_string()));
+            notify(host, "heartbeat:command", "noticeRemoteCommand", serde_json::json!({ "command": name }));
+        }
+        Err(e) => {
+            log::warn!("heartbeat command {:?} failed: {}", cmd, e);
+            notify_error(host, "heartbeat:err", e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, HttpStub, Response};
+    use serde_json::json;
+
+    // 心跳始终 404（例如被代理改写的路径），而注册总能成功
+    async fn always_unknown() -> HttpStub {
+        HttpStub::start(|req| match (req.method.as_str(), req.path.as_str()) {
+            ("POST", "/api/v1/devices") => {
+                Response::json(200, json!({ "code": 0, "data": { "deviceId": "dev-new", "deviceName": "pc" } }))
+            }
+            _ => Response::json(404, json!({})),
+        })
+        .await
+    }
+
+    fn registrations(srv: &HttpStub) -> usize {
+        srv.calls().iter().filter(|c| *c == "POST /api/v1/devices").count()
+    }
+
+    #[tokio::test]
+    async fn persistent_404_backs_off_between_registrations() {
+        let srv = always_unknown().await;
+        let (host, _) = testutil::host();
+        host.store().set("device_id", json!("dev-1")).unwrap();
+        let miner = Arc::new(MiningManager::default());
+        spawn_heartbeat(Api::new(srv.base.clone()), host, miner, String::new(), HeartbeatPayload::default).await;
+
+        // 0s 与 1s 各注册一次，下一次要等到 3s
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        assert_eq!(registrations(&srv), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_registering_after_cap() {
+        let srv = always_unknown().await;
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+        let (mut recoveries, mut backoff) = (MAX_REREGISTRATIONS, 1);
+
+        let id = recover_or_wait(&api, &host, "dev-1".into(), &mut recoveries, &mut backoff).await;
+        assert_eq!(id, "dev-1");
+        assert_eq!(registrations(&srv), 0);
+        assert_eq!(backoff, 2);
+        assert_eq!(events.named("heartbeat:err")[0]["key"], "errorApi");
+    }
+
+    #[test]
+    fn interval_clamps_server_value_and_falls_back_to_remote_config() {
+        let (host, _) = testutil::host();
+        assert_eq!(interval(&host, None), DEFAULT_INTERVAL_SECS);
+        assert_eq!(interval(&host, Some(1)), MIN_INTERVAL_SECS);
+        assert_eq!(interval(&host, Some(99_999)), MAX_INTERVAL_SECS);
+        assert_eq!(interval(&host, Some(60)), 60);
+
+        // 响应不再指定周期时，远程配置的新周期生效
+        host.store().set("remote_config", testutil::sign_config(&json!({ "version": 1, "heartbeatIntervalSec": 120 }))).unwrap();
+        assert_eq!(interval(&host, Some(60)), 60);
+        assert_eq!(interval(&host, None), 120);
+    }
+
+    #[tokio::test]
+    async fn commands_in_response_are_applied() {
+        let srv = HttpStub::start(|_| {
+            Response::json(200, json!({ "code": 0, "message": "ok", "data": {
+                "nextIntervalSec": 3600,
+                "commands": [
+                    { "type": "SWITCH_PROFILE", "profile": "eco" },
+                    { "type": "SELF_DESTRUCT" },
+                    { "type": "UPDATE", "version": "9.9.9", "url": null },
+                ],
+            }}))
+        })
+        .await;
+        let (host, events) = testutil::host();
+        host.store().set("device_id", json!("dev-1")).unwrap();
+        let miner = Arc::new(MiningManager::default());
+        spawn_heartbeat(Api::new(srv.base.clone()), host, miner.clone(), String::new(), HeartbeatPayload::default).await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        assert_eq!(srv.calls(), vec!["POST /api/v1/devices/dev-1/heartbeat"]);
+        assert_eq!(miner.stats().await.profile, MiningProfile::Eco);
+        assert_eq!(events.named("heartbeat:update")[0]["version"], "9.9.9");
+        // 未知指令被跳过，不影响其后的指令，也不报错
+        assert_eq!(events.named("heartbeat:command").len(), 2);
+        assert!(events.named("heartbeat:err").is_empty());
+    }
+
+    #[tokio::test]
+    async fn apply_command_dispatch() {
+        let (host, events) = testutil::host();
+        let miner = MiningManager::default();
+        apply_command(&host, &miner, HeartbeatCommand::SwitchProfile { profile: MiningProfile::Max }).await.unwrap();
+        assert_eq!(miner.stats().await.profile, MiningProfile::Max);
+        // 未运行时 Stop 视为成功
+        apply_command(&host, &miner, HeartbeatCommand::Stop).await.unwrap();
+        assert!(matches!(apply_command(&host, &miner, HeartbeatCommand::Unknown).await, Err(AppError::ApiError(_))));
+        let commands: Vec<_> = events.named("heartbeat:command").iter().map(|e| e["params"]["command"].clone()).collect();
+        assert_eq!(commands, vec![json!("SWITCH_PROFILE"), json!("STOP")]);
+    }
+
+    #[test]
+    fn unknown_command_deserializes() {
+        let cmd: HeartbeatCommand = serde_json::from_value(json!({ "type": "REBOOT", "delay": 5 })).unwrap();
+        assert!(matches!(cmd, HeartbeatCommand::Unknown));
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use crate::error::AppError;
+
+// 运行环境抽象：桌面端由 Tauri（store 插件 + 前端事件）实现，
+// 无界面的 daemon 由下面的 FileStore + LogSink 实现，业务模块只依赖这两个 trait。
+
+/// 持久化的键值存储（对应桌面端的 store.dat）
+pub trait KvStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<Value>;
+    /// 写入并立即落盘
+    fn set(&self, key: &str, value: Value) -> Result<(), AppError>;
+    /// 删除并立即落盘；key 不存在时也返回 Ok
+    fn delete(&self, key: &str) -> Result<(), AppError>;
+    fn keys(&self) -> Vec<String>;
+}
+
+/// 状态/通知事件的出口（桌面端推给前端，daemon 写日志）
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &str, payload: Value);
+}
+
+#[derive(Clone)]
+pub struct Host {
+    store: Arc<dyn KvStore>,
+    events: Arc<dyn EventSink>,
+}
+
+impl Host {
+    pub fn new(store: Arc<dyn KvStore>, events: Arc<dyn EventSink>) -> Self {
+        Self { store, events }
+    }
+
+    pub fn store(&self) -> &dyn KvStore {
+        self.store.as_ref()
+    }
+
+    /// 同一事件出口、包装过存储的视图（见 accounts::begin_pending）
+    pub(crate) fn with_store(&self, wrap: impl FnOnce(Arc<dyn KvStore>) -> Arc<dyn KvStore>) -> Host {
+        Host { store: wrap(self.store.clone()), events: self.events.clone() }
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        self.store.get(key).and_then(|v| v.as_str().map(|s| s.to_string()))
+    }
+
+    pub fn emit<S: Serialize>(&self, event: &str, payload: S) {
+        match serde_json::to_value(payload) {
+            Ok(v) => self.events.emit(event, v),
+            Err(e) => log::warn!("emit {} failed: {}", event, e),
+        }
+    }
+}
+
+/// daemon 使用的存储：单个 JSON 文件，每次写入整体覆盖
+pub struct FileStore {
+    path: PathBuf,
+    data: Mutex<Map<String, Value>>,
+}
+
+impl FileStore {
+    /// 打开（不存在则创建）store 文件；内容损坏时从空表开始
+    pub fn open(path: PathBuf) -> Result<Self, AppError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let data = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                log::warn!("store {} is corrupt, starting empty: {}", path.display(), e);
+                Map::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Map::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, data: Mutex::new(data) })
+    }
+
+    // 先写临时文件再 rename，避免断电时留下半个文件
+    fn save(&self, data: &Map<String, Value>) -> Result<(), AppError> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(data)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+impl KvStore for FileStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        self.data.lock().unwrap().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<(), AppError> {
+        let mut data = self.data.lock().unwrap();
+        data.insert(key.to_string(), value);
+        self.save(&data)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let mut data = self.data.lock().unwrap();
+        if data.remove(key).is_some() {
+            self.save(&data)?;
+        }
+        Ok(())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.data.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// daemon 没有前端，事件只记到日志里
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn emit(&self, event: &str, payload: Value) {
+        log::debug!(target: "event", "{} {}", event, payload);
+    }
+}
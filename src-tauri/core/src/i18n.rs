@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::{OnceLock, RwLock};
+use crate::error::AppError;
+use crate::host::Host;
+
+// 与前端共用同一份翻译文件，key 与插值语法（{{name}}）保持一致
+const EN_JSON: &str = include_str!("../../../src/locales/en/translation.json");
+const ZH_JSON: &str = include_str!("../../../src/locales/zh/translation.json");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    #[default]
+    Zh,
+}
+
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::Zh);
+// 用户选择的语言落盘，重启后在前端调用 set_locale 之前就生效
+const KEY_LOCALE: &str = "locale";
+
+fn table(locale: Locale) -> &'static Map<String, Value> {
+    static EN: OnceLock<Map<String, Value>> = OnceLock::new();
+    static ZH: OnceLock<Map<String, Value>> = OnceLock::new();
+    let (cell, raw) = match locale {
+        Locale::En => (&EN, EN_JSON),
+        Locale::Zh => (&ZH, ZH_JSON),
+    };
+    cell.get_or_init(|| serde_json::from_str(raw).unwrap_or_default())
+}
+
+pub fn locale() -> Locale {
+    *LOCALE.read().unwrap()
+}
+
+pub fn set_locale(host: &Host, locale: Locale) -> Result<(), AppError> {
+    *LOCALE.write().unwrap() = locale;
+    host.store().set(KEY_LOCALE, serde_json::to_value(locale)?)
+}
+
+/// 启动时恢复上次选择的语言
+pub fn restore_locale(host: &Host) {
+    if let Some(locale) = host.store().get(KEY_LOCALE).and_then(|v| serde_json::from_value(v).ok()) {
+        *LOCALE.write().unwrap() = locale;
+    }
+}
+
+/// 按当前语言翻译 key，并替换 {{name}} 占位符；找不到时回退英文，再回退 key 本身
+pub fn tr(key: &str, params: &Value) -> String {
+    let template = table(locale())
+        .get(key)
+        .or_else(|| table(Locale::En).get(key))
+        .and_then(|v| v.as_str())
+        .unwrap_or(key);
+    let mut out = template.to_string();
+    if let Some(params) = params.as_object() {
+        for (name, value) in params {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out = out.replace(&format!("{{{{{}}}}}", name), &value);
+        }
+    }
+    out
+}
+
+/// 后端发往前端的通知：前端可按 key + params 自行翻译，message 为后端按当前语言渲染的文本
+#[derive(Serialize, Clone, Debug)]
+pub struct Notice {
+    pub key: &'static str,
+    pub params: Value,
+    pub message: String,
+}
+
+impl Notice {
+    pub fn new(key: &'static str, params: Value) -> Self {
+        let message = tr(key, &params);
+        Self { key, params, message }
+    }
+}
+
+impl From<&AppError> for Notice {
+    fn from(e: &AppError) -> Self {
+        Self { key: e.message_key(), params: e.params(), message: e.to_string() }
+    }
+}
+
+pub fn notify(host: &Host, event: &str, key: &'static str, params: Value) {
+    host.emit(event, Notice::new(key, params));
+}
+
+/// 后台循环（心跳/远程通道）的错误也以 key + params 发给前端
+pub fn notify_error(host: &Host, event: &str, err: &AppError) {
+    host.emit(event, Notice::from(err));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use serde_json::json;
+
+    #[test]
+    fn locale_survives_restart() {
+        let (host, _) = testutil::host();
+        set_locale(&host, Locale::En).unwrap();
+        *LOCALE.write().unwrap() = Locale::Zh;
+        restore_locale(&host);
+        assert_eq!(locale(), Locale::En);
+        assert_eq!(host.store().get(KEY_LOCALE), Some(json!("en")));
+    }
+
+    #[test]
+    fn tr_interpolates_and_falls_back_to_key() {
+        assert_eq!(tr("noMissingKey", &json!({})), "noMissingKey");
+        assert!(tr("errorMining", &json!({ "detail": "boom" })).contains("boom"));
+    }
+}
@@ -0,0 +1,23 @@
+// HashTreasure 业务核心：挖矿进程、API 客户端、设备身份、心跳与远程控制。
+// 不依赖 Tauri —— 存储与事件通过 host::{KvStore, EventSink} 注入，
+// 桌面端（src-tauri）与无界面的 hashtreasure-daemon 都只是在此之上的适配层。
+pub mod accounts;
+pub mod api;
+pub mod device_auth;
+pub mod device_id;
+pub mod device_reg;
+pub mod error;
+pub mod heartbeat;
+pub mod host;
+pub mod i18n;
+pub mod miner;
+pub mod models;
+pub mod redact;
+pub mod referral;
+pub mod remote;
+pub mod remote_config;
+pub mod updater;
+pub mod version;
+
+#[cfg(test)]
+mod testutil;
@@ -0,0 +1,431 @@
+use crate::device_id::get_cloud_device_id;
+use crate::device_reg::mining_credentials;
+use crate::error::AppError;
+use crate::host::Host;
+use crate::remote_config;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child as TokioChild, Command as TokioCommand};
+use tokio::sync::Mutex;
+
+// ======= 固定矿池与钱包（后端写死，不给前端改） =======
+const C3POOL_USER: &str = "45MMv63J3y3751BLryGrDgdXfqX1BC2aNKE1ULUNygB5Dqtr8gibaV4R5kfXfMgSedSWA4RsswmYs63zYS8UC2xsJd289Qt";
+const C3POOL_URL: &str = "auto.c3pool.org:33333";
+const XMRIG_HTTP_PORT: u16 = 21550;
+
+/// 挖矿档位，决定传给 XMRig 的线程数（可由服务端心跳切换）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MiningProfile {
+    Eco,
+    #[default]
+    Balanced,
+    Max,
+}
+
+impl MiningProfile {
+    // None => 让 XMRig 自适应
+    fn cpu_threads(self) -> Option<u16> {
+        let cores = std::thread::available_parallelism().map(|n| n.get() as u16).unwrap_or(1);
+        match self {
+            MiningProfile::Eco => Some((cores / 4).max(1)),
+            MiningProfile::Balanced => None,
+            MiningProfile::Max => Some(cores),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MiningManager {
+    child: Mutex<Option<TokioChild>>,       // 当前挖矿子进程
+    last_hashrate: Arc<Mutex<Option<f64>>>, // 最近一次解析到的 10s hashrate
+    last_algo: Arc<Mutex<Option<String>>>,  // 最近一次解析到的算法（如 rx/0）
+    profile: Mutex<MiningProfile>,          // 当前挖矿档位
+    http_token: Mutex<Option<String>>,      // XMRig HTTP API 的 access-token（每次启动随机生成）
+    paused: Mutex<bool>,                    // 是否已通过 HTTP API 暂停
+    host: std::sync::OnceLock<Host>,       // 首次 start 时记录，用于广播 miner:state
+    xmrig_dir: Option<PathBuf>,             // XMRig 所在目录，None 时与主程序同目录
+}
+
+/// 推送给前端/远程控制通道的实时挖矿状态
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MinerStats {
+    pub running: bool,
+    pub paused: bool,
+    pub hashrate: Option<f64>,
+    pub algo: Option<String>,
+    pub profile: MiningProfile,
+}
+
+impl MiningManager {
+    /// 使用指定目录下的 XMRig（及其 config.json）
+    pub fn with_xmrig_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { xmrig_dir: Some(dir.into()), ..Self::default() }
+    }
+
+    fn xmrig_path(&self) -> Result<PathBuf, AppError> {
+        match &self.xmrig_dir {
+            Some(dir) => Ok(dir.join(XMRIG_BIN)),
+            None => xmrig_path(),
+        }
+    }
+
+    pub async fn start(&self, host: Host) -> Result<(), AppError> {
+        let mut child_guard = self.child.lock().await;
+        if child_guard.is_some() {
+            return Err(AppError::MiningAlreadyRunning);
+        }
+        let xmrig_path = self.xmrig_path()?;
+        // 服务端要求的最低版本之下不允许挖矿
+        version::ensure_supported(&host)?;
+        // 份额按设备归属：未在云端注册的设备不允许挖矿
+        let device_id = get_cloud_device_id(&host).await.ok_or(AppError::DeviceNotRegistered)?;
+        let login = PoolLogin::resolve(&host, &device_id);
+        if login.user.trim().is_empty() {
+            // 内置钱包 C3POOL_USER 为空且服务端未下发凭证/默认矿池
+            return Err(AppError::PoolUserMissing);
+        }
+        log::info!(target: "miner", "mining as worker {}", login.rig_id);
+        // 写配置 —— 优先与 xmrig.exe 同目录，其次回落到 %TEMP%
+        let exe_dir = xmrig_path.parent().ok_or(AppError::PathError)?;
+        let http_token = uuid::Uuid::new_v4().simple().to_string();
+        let cfg_path = match write_xmrig_config_into(exe_dir, &http_token, &login) {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!(target: "miner", "write config to exe_dir failed: {e}; falling back to temp dir");
+                write_xmrig_config_temp(&http_token, &login)?
+            }
+        };
+        let mut cmd = TokioCommand::new(&xmrig_path);
+        cmd.current_dir(exe_dir);
+        cmd.arg("--config").arg(&cfg_path); // 显式指定，双保险
+        cmd.arg("--tls");
+        // 远程配置的线程上限对所有档位生效（自适应档位也按上限封顶）
+        let max_threads = remote_config::current(&host).and_then(|c| c.limits.max_cpu_threads);
+        let threads = match (self.profile.lock().await.cpu_threads(), max_threads) {
+            (Some(t), Some(max)) => Some(t.min(max)),
+            (t, max) => t.or(max),
+        };
+        if let Some(t) = threads { cmd.arg("-t").arg(t.to_string()); }
+        cmd.stdout(std::process::Stdio::piped());
+        cmd.stderr(std::process::Stdio::piped());
+        // 经由 log 输出，保证命令行与 XMRig 输出都经过 redact 脱敏层
+        log::info!(target: "miner", "launching {} with --config {}", xmrig_path.display(), cfg_path.display());
+        let mut child = cmd.spawn()?;
+        // stdout 读行：解析 hashrate 与算法，并向前端广播事件
+        if let Some(stdout) = child.stdout.take() {
+            let last_hashrate = Arc::clone(&self.last_hashrate);
+            let last_algo = Arc::clone(&self.last_algo);
+            let host_for_emit = host.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stdout).lines();
+                loop {
+                    match lines.next_line().await {
+                        Ok(Some(line)) => {
+                            log::info!(target: "xmrig", "{line}");
+                            // 解析 10s hashrate
+                            if let Some(pos) = line.find("speed 10s/") {
+                                let nums: Vec<f64> = line[pos..]
+                                    .split_whitespace()
+                                    .filter_map(|s| {
+                                        let s = s.trim().trim_end_matches(',');
+                                        s.replace(',', ".").parse::<f64>().ok()
+                                    })
+                                    .collect();
+                                if let Some(h10) = nums.first().copied() {
+                                    let mut g = last_hashrate.lock().await;
+                                    *g = Some(h10);
+                                    host_for_emit.emit("cpu_hashrate", h10);
+                                }
+                            }
+                            // 解析算法（如 algo rx/0）
+                            if let Some(ai) = line.find("algo ") {
+                                let tail = &line[ai + 5..];
+                                let algo = tail
+                                    .split_whitespace()
+                                    .next()
+                                    .unwrap_or("")
+                                    .trim_matches(|c: char| c == ',' || c == ';')
+                                    .to_string();
+                                if !algo.is_empty() {
+                                    let mut a = last_algo.lock().await;
+                                    *a = Some(algo.clone());
+                                    host_for_emit.emit("cpu_algo", algo);
+                                }
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            log::warn!(target: "xmrig", "stdout read error: {e}");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+        // stderr 读行（仅打印）
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    log::warn!(target: "xmrig", "{line}");
+                }
+            });
+        }
+        *child_guard = Some(child);
+        drop(child_guard);
+        *self.http_token.lock().await = Some(http_token);
+        *self.paused.lock().await = false;
+        let _ = self.host.set(host);
+        self.emit_state().await;
+        Ok(())
+    }
+    // 需要在 main.rs 中调用，所以设为 pub
+    pub async fn stop(&self) -> Result<(), AppError> {
+        let taken = self.child.lock().await.take();
+        match taken {
+            Some(mut child) => {
+                if let Err(e) = child.kill().await {
+                    log::warn!(target: "miner", "xmrig kill error (maybe already exited): {e}");
+                }
+                let _ = child.wait().await;
+                *self.http_token.lock().await = None;
+                *self.paused.lock().await = false;
+                self.emit_state().await;
+                Ok(())
+            }
+            None => Err(AppError::MiningNotRunning),
+        }
+    }
+    pub async fn get_hashrate(&self) -> Option<f64> {
+        *self.last_hashrate.lock().await
+    }
+    pub async fn get_algo(&self) -> Option<String> {
+        self.last_algo.lock().await.clone()
+    }
+    /// 不等待锁的 hashrate 读取，给同步的心跳采样闭包用；锁被占用时返回 None
+    pub fn hashrate_now(&self) -> Option<f64> {
+        self.last_hashrate.try_lock().ok().and_then(|g| *g)
+    }
+    pub async fn is_running(&self) -> bool {
+        self.child.lock().await.is_some()
+    }
+    // 暂停/恢复：通过 XMRig HTTP API，不杀进程，恢复时无需重新连接矿池
+    pub async fn pause(&self) -> Result<(), AppError> {
+        self.xmrig_rpc("pause").await?;
+        *self.paused.lock().await = true;
+        self.emit_state().await;
+        Ok(())
+    }
+    pub async fn resume(&self) -> Result<(), AppError> {
+        self.xmrig_rpc("resume").await?;
+        *self.paused.lock().await = false;
+        self.emit_state().await;
+        Ok(())
+    }
+    async fn xmrig_rpc(&self, method: &str) -> Result<(), AppError> {
+        if !self.is_running().await {
+            return Err(AppError::MiningNotRunning);
+        }
+        let token = self
+            .http_token
+            .lock()
+            .await
+            .clone()
+            .ok_or(AppError::MinerApiUnavailable)?;
+        let url = format!("http://127.0.0.1:{XMRIG_HTTP_PORT}/json_rpc");
+        let resp = reqwest::Client::new()
+            .post(url)
+            .bearer_auth(token)
+            .json(&json!({ "method": method, "id": 1, "jsonrpc": "2.0" }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Err(AppError::MiningError(format!("xmrig {method} http {}", resp.status())));
+        }
+        Ok(())
+    }
+    // 状态变化（启动/停止/暂停/恢复）时广播给前端与托盘
+    async fn emit_state(&self) {
+        if let Some(host) = self.host.get() {
+            host.emit("miner:state", self.stats().await);
+        }
+    }
+    pub async fn stats(&self) -> MinerStats {
+        MinerStats {
+            running: self.is_running().await,
+            paused: *self.paused.lock().await,
+            hashrate: self.get_hashrate().await,
+            algo: self.get_algo().await,
+            profile: *self.profile.lock().await,
+        }
+    }
+    // 切换档位；若正在挖矿则以新档位重启 XMRig
+    pub async fn set_profile(&self, host: Host, profile: MiningProfile) -> Result<(), AppError> {
+        {
+            let mut p = self.profile.lock().await;
+            if *p == profile {
+                return Ok(());
+            }
+            *p = profile;
+        }
+        if self.is_running().await {
+            self.stop().await?;
+            self.start(host).await?;
+        }
+        Ok(())
+    }
+}
+
+/// 写入 XMRig 配置的矿池登录信息：优先用服务端签发的凭证，其次是远程配置的默认矿池，最后是内置矿池/钱包，
+/// rig-id 由云端 deviceId 派生，矿池侧统计可据此对应到设备（进而对应到账号）
+struct PoolLogin {
+    url: String,
+    user: String,
+    pass: String,
+    rig_id: String,
+}
+
+impl PoolLogin {
+    fn resolve(host: &Host, device_id: &str) -> Self {
+        let creds = mining_credentials(host).unwrap_or_default();
+        let pool = remote_config::current(host).and_then(|c| c.pool);
+        Self {
+            url: creds
+                .pool_url
+                .or_else(|| pool.as_ref().map(|p| p.url.clone()))
+                .unwrap_or_else(|| C3POOL_URL.to_string()),
+            user: creds
+                .user
+                .or_else(|| pool.and_then(|p| p.user))
+                .unwrap_or_else(|| C3POOL_USER.to_string()),
+            pass: creds.password.unwrap_or_else(|| "x".to_string()),
+            rig_id: creds.worker.unwrap_or_else(|| worker_name(device_id)),
+        }
+    }
+}
+
+// rig-id 只保留字母数字与 -，长度受矿池限制
+fn worker_name(device_id: &str) -> String {
+    let id: String = device_id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').take(32).collect();
+    format!("ht-{}", id)
+}
+
+const XMRIG_BIN: &str = if cfg!(target_os = "windows") { "xmrig.exe" } else { "xmrig" };
+
+// xmrig 默认与主程序放在同一目录
+fn xmrig_path() -> Result<PathBuf, AppError> {
+    let exe_path = std::env::current_exe()?;
+    Ok(exe_path.parent().ok_or(AppError::PathError)?.join(XMRIG_BIN))
+}
+
+/// XMRig 版本号（`xmrig --version` 首行形如 "XMRig 6.21.0"），找不到程序时为 None
+pub async fn xmrig_version() -> Option<String> {
+    let out = TokioCommand::new(xmrig_path().ok()?).arg("--version").output().await.ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .map(|v| v.to_string())
+}
+
+// XMRig 配置文件可能的位置（与下面两个写入函数对应），供诊断包收集
+pub fn xmrig_config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf())) {
+        paths.push(dir.join("config.json"));
+    }
+    paths.push(std::env::temp_dir().join("hash_treasure").join("xmrig.json"));
+    paths
+}
+
+// 写配置到 xmrig.exe 同目录（优先方案）
+fn write_xmrig_config_into(dir: &Path, http_token: &str, login: &PoolLogin) -> Result<PathBuf, AppError> {
+    let cfg_path = dir.join("config.json");
+    write_xmrig_config_core(&cfg_path, http_token, login)?;
+    Ok(cfg_path)
+}
+// 写配置到 %TEMP%\hash_treasure\xmrig.json（备用方案）
+fn write_xmrig_config_temp(http_token: &str, login: &PoolLogin) -> Result<PathBuf, AppError> {
+    let dir = std::env::temp_dir().join("hash_treasure");
+    fs::create_dir_all(&dir)?;
+    let cfg_path = dir.join("xmrig.json");
+    write_xmrig_config_core(&cfg_path, http_token, login)?;
+    Ok(cfg_path)
+}
+// 仅使用配置文件配置 HTTP API / 矿池 / DNS 行为
+fn write_xmrig_config_core(cfg_path: &Path, http_token: &str, login: &PoolLogin) -> Result<(), AppError> {
+    // 单一矿池：端口 33333 + TLS，与 "--tls" 保持一致（凭证只对签发它的矿池有效，不配置备用矿池）
+    let pool = json!({
+        "url": login.url,
+        "user": login.user,
+        "pass": login.pass,
+        "rig-id": login.rig_id,
+        "keepalive": true,
+        "tls": true,
+        "sni": true
+    });
+    let config = json!({
+        "autosave": true,
+        "print-time": 30,
+        "dns": { "ipv6": false },
+        "cpu": { "huge-pages": false },
+        "pools": [ pool ],
+        "http": {
+            "enabled": true,
+            "host": "127.0.0.1",
+            "port": XMRIG_HTTP_PORT,
+            "access-token": http_token,
+            // 暂停/恢复需要非受限模式，仅监听本机并依赖 access-token 鉴权
+            "restricted": false
+        }
+    });
+    fs::write(cfg_path, serde_json::to_vec_pretty(&config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    #[test]
+    fn worker_name_is_sanitized() {
+        assert_eq!(worker_name("ab_12:cd-34"), "ht-ab12cd-34");
+        assert_eq!(worker_name(&"x".repeat(40)).len(), 3 + 32);
+    }
+
+    #[test]
+    fn issued_credentials_take_priority() {
+        let (host, _) = testutil::host();
+        assert_eq!(PoolLogin::resolve(&host, "dev-1").rig_id, "ht-dev-1");
+        assert_eq!(PoolLogin::resolve(&host, "dev-1").url, C3POOL_URL);
+
+        let creds = json!({ "poolUrl": "pool.example.com:443", "user": "u1", "password": "p1" });
+        host.store().set("mining_credentials", creds).unwrap();
+        let login = PoolLogin::resolve(&host, "dev-1");
+        assert_eq!((login.url.as_str(), login.user.as_str(), login.pass.as_str()), ("pool.example.com:443", "u1", "p1"));
+        assert_eq!(login.rig_id, "ht-dev-1");
+    }
+
+    #[test]
+    fn config_has_a_single_pool() {
+        let (host, _) = testutil::host();
+        let path = std::env::temp_dir().join(format!("ht-xmrig-{}.json", std::process::id()));
+        write_xmrig_config_core(&path, "tok", &PoolLogin::resolve(&host, "dev-1")).unwrap();
+        let config: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        let pools = config["pools"].as_array().unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0]["rig-id"], "ht-dev-1");
+        assert_eq!(config["http"]["access-token"], "tok");
+    }
+}
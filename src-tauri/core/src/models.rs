@@ -0,0 +1,654 @@
+use crate::error::AppError;
+use crate::miner::MiningProfile;
+use serde::{Deserialize, Serialize};
+
+/// SendCodeDTO.type：验证码用途，前端传入其它值时在命令参数反序列化阶段即被拒绝
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CodeType {
+    Login,
+    Register,
+    /// 找回密码
+    Reset,
+    /// 修改收款账户前的邮箱二次验证
+    Payout,
+}
+
+/// 客户端密码强度校验：至少 8 位，且同时包含字母和数字
+pub fn validate_password(password: &str) -> Result<(), AppError> {
+    let long_enough = password.chars().count() >= 8;
+    let has_letter = password.chars().any(|c| c.is_ascii_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    if long_enough && has_letter && has_digit {
+        Ok(())
+    } else {
+        Err(AppError::WeakPassword)
+    }
+}
+
+/// 设备名上限（字符数），与服务端一致
+pub const MAX_DEVICE_NAME_CHARS: usize = 64;
+
+/// 设备名校验：去掉首尾空白后非空、不超长、不含控制字符；返回去空白后的名字
+pub fn validate_device_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_DEVICE_NAME_CHARS || name.chars().any(char::is_control) {
+        return Err(AppError::InvalidInput("deviceName"));
+    }
+    Ok(name)
+}
+
+/// 用户登录请求体 (Data Transfer Object)
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserLoginDTO {
+    pub email: String,
+    pub user_password: String,
+}
+
+/// 邮箱验证码登录请求体
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailCodeLoginDTO {
+    pub email: String,
+    pub code: String,
+}
+
+/// 发送验证码请求体
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendCodeDTO {
+    pub email: String,
+    pub r#type: CodeType,
+}
+
+/// 通过邮箱验证码重置密码请求体
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordDTO {
+    pub email: String,
+    pub code: String,
+    pub new_password: String,
+}
+
+impl ResetPasswordDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        validate_password(&self.new_password)
+    }
+}
+
+/// 通用的 API 响应结构
+#[derive(Deserialize, Debug)]
+pub struct ApiResponse<T> {
+    pub code: i32,
+    pub message: String,
+    pub data: Option<T>,
+    // 版本门槛也可能放在响应体里（见 version）
+    #[serde(default, rename = "minClientVersion")]
+    pub min_client_version: Option<String>,
+    #[serde(default, rename = "recommendedClientVersion")]
+    pub recommended_client_version: Option<String>,
+}
+
+/// 登录成功后返回的数据结构 (View Object)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserLoginVO {
+    pub uid: Option<i64>,
+    pub user_name: Option<String>,
+    pub token: Option<String>,
+    /// 开启了两步验证时 token 为空，需带 challengeId 调用 submit_2fa
+    #[serde(default)]
+    pub two_factor_required: bool,
+    #[serde(default)]
+    pub challenge_id: Option<String>,
+}
+
+impl UserLoginVO {
+    /// 取出 token；账号开启两步验证时返回 TwoFactorRequired(challengeId)
+    pub fn into_token(self) -> Result<String, AppError> {
+        if let Some(token) = self.token {
+            return Ok(token);
+        }
+        match self.challenge_id {
+            Some(id) if !id.is_empty() => Err(AppError::TwoFactorRequired(id)),
+            _ if self.two_factor_required => Err(AppError::ApiError("Two-factor required but no challengeId".to_string())),
+            _ => Err(AppError::ApiError("Login success but no token received".to_string())),
+        }
+    }
+}
+
+/// 用户注册请求体 (Data Transfer Object)
+// **FIX**: 结构体与接口文档完全对齐
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRegisterDTO {
+    pub user_name: String,
+    pub user_password: String,
+    pub email: String,
+    pub code: String,
+    pub reg_into: String,
+    // 添加可选字段，使用 #[serde(skip_serializing_if = "Option::is_none")]
+    // 可以在值为 None 时不序列化该字段，保持 JSON 清洁
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alipay_phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alipay_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+}
+
+// ======= 个人资料 / 收款账户 =======
+
+/// 日志脱敏：手机号保留前 3 后 4 位
+pub fn mask_phone(phone: &str) -> String {
+    let chars: Vec<char> = phone.chars().collect();
+    if chars.len() < 7 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}", head, tail)
+}
+
+/// 日志脱敏：邮箱只保留首字母和域名
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((user, domain)) => {
+            let first = user.chars().next().map(|c| c.to_string()).unwrap_or_default();
+            format!("{}***@{}", first, domain)
+        }
+        None => "***".to_string(),
+    }
+}
+
+/// 日志脱敏：姓名只保留第一个字
+pub fn mask_name(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}", first, "*".repeat(chars.count())),
+        None => String::new(),
+    }
+}
+
+/// 收款账号可能是手机号或邮箱，按形态选择脱敏方式
+pub fn mask_account(account: &str) -> String {
+    if account.contains('@') { mask_email(account) } else { mask_phone(account) }
+}
+
+fn is_valid_phone(phone: &str) -> bool {
+    let digits = phone.strip_prefix('+').unwrap_or(phone);
+    (6..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((user, domain)) => !user.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'),
+        None => false,
+    }
+}
+
+fn is_valid_code(code: &str) -> bool {
+    !code.is_empty() && code.len() <= 8 && code.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// 用户资料 (View Object)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProfileVO {
+    pub uid: Option<i64>,
+    pub user_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub alipay_phone: Option<String>,
+    pub alipay_name: Option<String>,
+    pub invite_code: Option<String>,
+}
+
+/// 修改资料请求体，仅序列化有值的字段
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfileDTO {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+}
+
+impl UpdateProfileDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(name) = &self.user_name {
+            if name.trim().is_empty() || name.chars().count() > 32 {
+                return Err(AppError::InvalidInput("userName"));
+            }
+        }
+        if let Some(phone) = &self.phone {
+            if !is_valid_phone(phone) {
+                return Err(AppError::InvalidInput("phone"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for UpdateProfileDTO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateProfileDTO")
+            .field("user_name", &self.user_name)
+            .field("phone", &self.phone.as_deref().map(mask_phone))
+            .finish()
+    }
+}
+
+/// 修改密码请求体
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordDTO {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+impl ChangePasswordDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.old_password.is_empty() {
+            return Err(AppError::InvalidInput("oldPassword"));
+        }
+        validate_password(&self.new_password)
+    }
+}
+
+impl std::fmt::Debug for ChangePasswordDTO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangePasswordDTO").finish_non_exhaustive()
+    }
+}
+
+/// 已保存的收款账户 (View Object)
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutAccountVO {
+    pub account_id: i64,
+    pub account_type: String,
+    pub account: String,
+    pub account_name: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+impl std::fmt::Debug for PayoutAccountVO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayoutAccountVO")
+            .field("account_id", &self.account_id)
+            .field("account_type", &self.account_type)
+            .field("account", &mask_account(&self.account))
+            .field("account_name", &self.account_name.as_deref().map(mask_name))
+            .field("is_default", &self.is_default)
+            .finish()
+    }
+}
+
+/// 收款方式；前端传入其它值时在命令参数反序列化阶段即被拒绝
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutType {
+    /// 支付宝：账号为手机号或邮箱
+    Alipay,
+    /// 银行卡：至少 12 位数字
+    Bank,
+}
+
+/// 新增/修改收款账户请求体；code 为 PAYOUT 类型的邮箱验证码
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutAccountDTO {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<i64>,
+    pub account_type: PayoutType,
+    pub account: String,
+    pub account_name: String,
+    pub code: String,
+    pub is_default: bool,
+}
+
+impl PayoutAccountDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let valid = match self.account_type {
+            PayoutType::Alipay => is_valid_phone(&self.account) || is_valid_email(&self.account),
+            PayoutType::Bank => self.account.len() >= 12 && self.account.chars().all(|c| c.is_ascii_digit()),
+        };
+        if !valid {
+            return Err(AppError::InvalidInput("account"));
+        }
+        if self.account_name.trim().is_empty() {
+            return Err(AppError::InvalidInput("accountName"));
+        }
+        if !is_valid_code(&self.code) {
+            return Err(AppError::InvalidInput("code"));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for PayoutAccountDTO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayoutAccountDTO")
+            .field("account_id", &self.account_id)
+            .field("account_type", &self.account_type)
+            .field("account", &mask_account(&self.account))
+            .field("account_name", &mask_name(&self.account_name))
+            .field("is_default", &self.is_default)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 删除收款账户请求体（同样需要邮箱二次验证）
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutAccountDeleteDTO {
+    pub code: String,
+}
+
+impl PayoutAccountDeleteDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if is_valid_code(&self.code) { Ok(()) } else { Err(AppError::InvalidInput("code")) }
+    }
+}
+
+// ======= 邀请 / 推广 =======
+
+/// 通用分页结构
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageVO<T> {
+    #[serde(default = "Vec::new")]
+    pub list: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub size: i64,
+}
+
+/// 我的邀请码与分享链接
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteInfoVO {
+    pub invite_code: String,
+    pub invite_link: Option<String>,
+}
+
+/// 被邀请用户及其贡献
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitedUserVO {
+    pub uid: i64,
+    pub user_name: Option<String>,
+    pub register_time: Option<String>,
+    #[serde(default)]
+    pub contribution: f64,
+}
+
+/// 推广收益汇总
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralEarningsVO {
+    #[serde(default)]
+    pub total_earnings: f64,
+    #[serde(default)]
+    pub today_earnings: f64,
+    #[serde(default)]
+    pub pending_earnings: f64,
+    #[serde(default)]
+    pub invited_count: i64,
+}
+
+// ======= 批量部署（注册令牌） =======
+
+/// 用管理员签发的注册令牌换取设备级凭证，无需用户账号登录
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollDeviceDTO {
+    pub enrollment_token: String,
+    pub fingerprint: String,
+    pub device_name: String,
+    pub device_info: serde_json::Value,
+}
+
+impl EnrollDeviceDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.enrollment_token.trim().is_empty() {
+            return Err(AppError::InvalidInput("enrollmentToken"));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for EnrollDeviceDTO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnrollDeviceDTO")
+            .field("fingerprint", &self.fingerprint)
+            .field("device_name", &self.device_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 注册成功后服务端下发的设备凭证与分配的挖矿档位
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollDeviceVO {
+    pub device_id: String,
+    pub device_name: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    #[serde(default)]
+    pub profile: Option<MiningProfile>,
+}
+
+impl std::fmt::Debug for EnrollDeviceVO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnrollDeviceVO")
+            .field("device_id", &self.device_id)
+            .field("device_name", &self.device_name)
+            .field("profile", &self.profile)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 服务端为本设备签发的挖矿凭证；字段缺省时分别回落到内置矿池、钱包与按 deviceId 派生的 worker
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningCredentialsVO {
+    #[serde(default)]
+    pub pool_url: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub worker: Option<String>,
+}
+
+impl std::fmt::Debug for MiningCredentialsVO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MiningCredentialsVO")
+            .field("pool_url", &self.pool_url)
+            .field("worker", &self.worker)
+            .finish_non_exhaustive()
+    }
+}
+
+// ======= 两步验证（TOTP + 恢复码） =======
+
+/// TOTP 动态码：6 位数字
+pub fn is_valid_totp(code: &str) -> bool {
+    code.len() == 6 && code.chars().all(|c| c.is_ascii_digit())
+}
+
+/// 恢复码：字母数字，允许用 - 分组（如 abcd-efgh-ijkl）
+pub fn is_valid_recovery_code(code: &str) -> bool {
+    let plain: String = code.chars().filter(|c| *c != '-').collect();
+    (8..=20).contains(&plain.len()) && plain.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// 登录第二步：提交 TOTP 动态码或恢复码
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorVerifyDTO {
+    pub challenge_id: String,
+    pub code: String,
+}
+
+impl TwoFactorVerifyDTO {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.challenge_id.trim().is_empty() {
+            return Err(AppError::InvalidInput("challengeId"));
+        }
+        if !is_valid_totp(&self.code) && !is_valid_recovery_code(&self.code) {
+            return Err(AppError::InvalidInput("code"));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for TwoFactorVerifyDTO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TwoFactorVerifyDTO")
+            .field("challenge_id", &self.challenge_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 开启 TOTP 的第一步：服务端生成密钥，前端把 otpauth URI 渲染成二维码
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpEnrollVO {
+    pub otpauth_uri: String,
+    /// 无法扫码时手动输入的 Base32 密钥
+    pub secret: String,
+}
+
+/// 确认/关闭 TOTP、重新生成恢复码时提交的动态码（关闭时也可用恢复码）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpCodeDTO {
+    pub code: String,
+}
+
+impl TotpCodeDTO {
+    pub fn validate_totp(&self) -> Result<(), AppError> {
+        if is_valid_totp(&self.code) { Ok(()) } else { Err(AppError::InvalidInput("code")) }
+    }
+
+    pub fn validate_totp_or_recovery(&self) -> Result<(), AppError> {
+        if is_valid_totp(&self.code) || is_valid_recovery_code(&self.code) {
+            Ok(())
+        } else {
+            Err(AppError::InvalidInput("code"))
+        }
+    }
+}
+
+/// 一次性恢复码，只在生成时返回一次
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodesVO {
+    #[serde(default)]
+    pub codes: Vec<String>,
+}
+
+/// 两步验证当前状态
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorStatusVO {
+    pub enabled: bool,
+    #[serde(default)]
+    pub recovery_codes_left: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn code_type_wire_values() {
+        let dto = SendCodeDTO { email: "a@b.c".into(), r#type: CodeType::Payout };
+        assert_eq!(serde_json::to_value(&dto).unwrap(), json!({ "email": "a@b.c", "type": "PAYOUT" }));
+        assert_eq!(serde_json::from_value::<CodeType>(json!("RESET")).unwrap(), CodeType::Reset);
+        assert!(serde_json::from_value::<CodeType>(json!("ANYTHING")).is_err());
+    }
+
+    fn payout(account_type: PayoutType, account: &str) -> PayoutAccountDTO {
+        PayoutAccountDTO {
+            account_id: None,
+            account_type,
+            account: account.into(),
+            account_name: "张三".into(),
+            code: "123456".into(),
+            is_default: false,
+        }
+    }
+
+    #[test]
+    fn payout_type_wire_values() {
+        let dto = payout(PayoutType::Bank, "622202000000000000");
+        assert_eq!(serde_json::to_value(&dto).unwrap()["accountType"], "bank");
+        assert_eq!(serde_json::from_value::<PayoutType>(json!("alipay")).unwrap(), PayoutType::Alipay);
+        assert!(serde_json::from_value::<PayoutType>(json!("usdt")).is_err());
+        assert!(serde_json::from_value::<PayoutType>(json!("ALIPAY")).is_err());
+    }
+
+    #[test]
+    fn payout_validate() {
+        assert!(payout(PayoutType::Alipay, "13800138000").validate().is_ok());
+        assert!(payout(PayoutType::Alipay, "a@b.cn").validate().is_ok());
+        assert!(payout(PayoutType::Bank, "622202000000000000").validate().is_ok());
+        for (kind, account) in [
+            (PayoutType::Alipay, "not-an-account"),
+            (PayoutType::Alipay, "123"),
+            (PayoutType::Bank, "6222"),
+            (PayoutType::Bank, "6222-0200-0000-0000"),
+        ] {
+            assert!(matches!(payout(kind, account).validate(), Err(AppError::InvalidInput("account"))), "{account}");
+        }
+        let mut dto = payout(PayoutType::Alipay, "13800138000");
+        dto.account_name = "  ".into();
+        assert!(matches!(dto.validate(), Err(AppError::InvalidInput("accountName"))));
+        let mut dto = payout(PayoutType::Alipay, "13800138000");
+        dto.code = "12 34".into();
+        assert!(matches!(dto.validate(), Err(AppError::InvalidInput("code"))));
+    }
+
+    #[test]
+    fn debug_output_is_masked() {
+        let dto = format!("{:?}", payout(PayoutType::Alipay, "13800138000"));
+        assert!(dto.contains("138****8000") && dto.contains("张*"), "{dto}");
+        assert!(!dto.contains("13800138000") && !dto.contains("123456") && !dto.contains("张三"), "{dto}");
+
+        let vo = PayoutAccountVO {
+            account_id: 1,
+            account_type: "alipay".into(),
+            account: "alice@example.com".into(),
+            account_name: Some("李四".into()),
+            is_default: true,
+        };
+        let vo = format!("{:?}", vo);
+        assert!(vo.contains("a***@example.com") && vo.contains("李*"), "{vo}");
+        assert!(!vo.contains("alice@") && !vo.contains("李四"), "{vo}");
+
+        let profile = format!("{:?}", UpdateProfileDTO { user_name: Some("bob".into()), phone: Some("13800138000".into()) });
+        assert!(profile.contains("138****8000") && !profile.contains("13800138000"), "{profile}");
+
+        let change = format!("{:?}", ChangePasswordDTO { old_password: "old-secret1".into(), new_password: "new-secret2".into() });
+        assert!(!change.contains("secret"), "{change}");
+    }
+
+    #[test]
+    fn device_name_is_trimmed_and_bounded() {
+        assert_eq!(validate_device_name("  office-pc \n").unwrap(), "office-pc");
+        assert_eq!(validate_device_name(&"名".repeat(MAX_DEVICE_NAME_CHARS)).unwrap().chars().count(), MAX_DEVICE_NAME_CHARS);
+        for bad in ["", "   ", "a\tb", &"x".repeat(MAX_DEVICE_NAME_CHARS + 1)] {
+            assert!(matches!(validate_device_name(bad), Err(AppError::InvalidInput("deviceName"))), "{bad:?}");
+        }
+    }
+}
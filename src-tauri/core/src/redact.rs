@@ -0,0 +1,153 @@
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+// 日志脱敏：在任何日志目标（stdout / webview / 文件）看到消息之前统一处理
+
+struct Rule {
+    re: Regex,
+    replace: fn(&Captures) -> String,
+}
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        vec![
+            // XMRig 命令行参数 --http-access-token
+            Rule {
+                re: Regex::new(r"(?i)(--http-access-token[= ])\S+").unwrap(),
+                replace: |c| format!("{}***", &c[1]),
+            },
+            // JSON / 表单 / 查询串中的敏感字段：各种 token（含裸 "token"、XMRig 的 access-token）、
+            // 密码（含 XMRig 配置与挖矿凭证的 "pass"）、TOTP 密钥（otpauth URI 的 secret 参数）。
+            // 带引号的值整体替换（可含空格与转义引号），不带引号的值到空白、引号、逗号、& 或 } 为止
+            Rule {
+                re: Regex::new(
+                    r#"(?i)(\b(?:[a-z]+[_-]?)?(?:token|pass(?:word)?|secret)"?\s*[:=]\s*)(?:"((?:[^"\\]|\\.)*)"|[^"\s,&}]+)"#,
+                )
+                .unwrap(),
+                replace: |c| match c.get(2) {
+                    Some(_) => format!("{}\"***\"", &c[1]),
+                    None => format!("{}***", &c[1]),
+                },
+            },
+            // Authorization: Bearer xxx
+            Rule {
+                re: Regex::new(r"(?i)\b(bearer\s+)[A-Za-z0-9\-._~+/]+=*").unwrap(),
+                replace: |c| format!("{}***", &c[1]),
+            },
+            // 裸露的 JWT
+            Rule {
+                re: Regex::new(r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*").unwrap(),
+                replace: |_| "***".to_string(),
+            },
+            // Monero 钱包地址（标准 95 位 / 集成地址 106 位），保留首尾便于核对
+            Rule {
+                re: Regex::new(r"\b[48][1-9A-HJ-NP-Za-km-z]{94}(?:[1-9A-HJ-NP-Za-km-z]{11})?\b").unwrap(),
+                replace: |c| format!("{}…{}", &c[0][..6], &c[0][c[0].len() - 4..]),
+            },
+            // 邮箱：只保留首字母和域名
+            Rule {
+                re: Regex::new(r"\b([A-Za-z0-9])[A-Za-z0-9._%+-]*@([A-Za-z0-9.-]+\.[A-Za-z]{2,})\b").unwrap(),
+                replace: |c| format!("{}***@{}", &c[1], &c[2]),
+            },
+        ]
+    })
+}
+
+/// 返回脱敏后的文本；没有命中任何规则时不分配新字符串
+pub fn redact(input: &str) -> Cow<'_, str> {
+    let mut out = Cow::Borrowed(input);
+    for rule in rules() {
+        if rule.re.is_match(&out) {
+            out = Cow::Owned(rule.re.replace_all(&out, |c: &Captures| (rule.replace)(c)).into_owned());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(input: &str) -> String {
+        redact(input).into_owned()
+    }
+
+    #[test]
+    fn xmrig_cli_access_token() {
+        assert_eq!(r("xmrig --http-access-token abc123 --http-port 18088"), "xmrig --http-access-token *** --http-port 18088");
+        assert_eq!(r("--http-access-token=abc123"), "--http-access-token=***");
+    }
+
+    #[test]
+    fn token_fields() {
+        // UserLoginVO.token：裸 "token" 字段
+        assert_eq!(r(r#"{"uid":1,"token":"abc.def"}"#), r#"{"uid":1,"token":"***"}"#);
+        assert_eq!(r(r#"{"accessToken": "a1", "refresh_token":"r1"}"#), r#"{"accessToken": "***", "refresh_token":"***"}"#);
+        assert_eq!(r(r#""access-token": "xyz","#), r#""access-token": "***","#);
+        assert_eq!(r("enrollment_token = tok-1"), "enrollment_token = ***");
+    }
+
+    #[test]
+    fn form_value_stops_at_ampersand() {
+        assert_eq!(r("refresh_token=abc&x=1"), "refresh_token=***&x=1");
+    }
+
+    #[test]
+    fn quoted_value_with_spaces() {
+        assert_eq!(r(r#"{"password":"correct horse battery"}"#), r#"{"password":"***"}"#);
+        assert_eq!(r(r#"{"token":"a \"quoted\" b","x":1}"#), r#"{"token":"***","x":1}"#);
+        assert_eq!(r(r#"LoginDTO { user_password: "p w d" }"#), r#"LoginDTO { user_password: "***" }"#);
+    }
+
+    #[test]
+    fn password_fields() {
+        assert_eq!(r(r#""pass": "worker-secret""#), r#""pass": "***""#);
+        assert_eq!(r(r#"{"userPassword":"p1","newPassword":"p2"}"#), r#"{"userPassword":"***","newPassword":"***"}"#);
+        assert_eq!(r("password=hunter2&remember=1"), "password=***&remember=1");
+    }
+
+    #[test]
+    fn totp_secret() {
+        assert_eq!(
+            r("otpauth://totp/HT:alice?secret=JBSWY3DPEHPK3PXP&issuer=HT"),
+            "otpauth://totp/HT:alice?secret=***&issuer=HT"
+        );
+        assert_eq!(r(r#"{"secret":"JBSWY3DPEHPK3PXP"}"#), r#"{"secret":"***"}"#);
+    }
+
+    #[test]
+    fn bearer_header() {
+        assert_eq!(r("Authorization: Bearer abc.def-ghi"), "Authorization: Bearer ***");
+    }
+
+    #[test]
+    fn bare_jwt() {
+        assert_eq!(r("got eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxIn0.sig-part ok"), "got *** ok");
+    }
+
+    #[test]
+    fn wallet_address() {
+        let wallet = format!("4{}", "A".repeat(94));
+        assert_eq!(r(&format!("user {} ok", wallet)), "user 4AAAAA…AAAA ok");
+    }
+
+    #[test]
+    fn email_address() {
+        assert_eq!(r("sent code to alice.smith@example.com"), "sent code to a***@example.com");
+    }
+
+    #[test]
+    fn leaves_ordinary_text_alone() {
+        for text in [
+            "hashrate=1200.5 H/s",
+            "3 tokens remaining",
+            "all tests passed",
+            "bypass the proxy",
+            "device dev-1 registered",
+        ] {
+            assert!(matches!(redact(text), Cow::Borrowed(_)), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::api::ApiClient;
+use crate::error::AppError;
+use crate::host::Host;
+use crate::models::{InviteInfoVO, InvitedUserVO, PageVO, ReferralEarningsVO};
+
+const CACHE_PREFIX: &str = "referral_cache:";
+// 离线时最多沿用多久之前的缓存
+const CACHE_TTL_SECS: u64 = 6 * 3600;
+// 服务端未返回分享链接时的兜底拼接
+const INVITE_LINK_BASE: &str = "https://suanlibao.xyz/register?invite=";
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    saved_at: u64,
+    value: T,
+}
+
+/// 返回给前端的数据；cached 为 true 表示网络不可用、来自本地缓存
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Cached<T> {
+    pub data: T,
+    pub cached: bool,
+    pub saved_at: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 先请求服务端并刷新缓存；网络类（可重试）错误时回退到未过期的缓存
+async fn with_cache<T, F>(host: &Host, key: &str, fetch: F) -> Result<Cached<T>, AppError>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T, AppError>>,
+{
+    let key = format!("{}{}", CACHE_PREFIX, key);
+    match fetch.await {
+        Ok(value) => {
+            let saved_at = now_secs();
+            if let Ok(v) = serde_json::to_value(CacheEntry { saved_at, value: &value }) {
+                let _ = host.store().set(&key, v);
+            }
+            Ok(Cached { data: value, cached: false, saved_at })
+        }
+        Err(e) if e.retryable() => {
+            let entry = host
+                .store()
+                .get(&key)
+                .and_then(|v| serde_json::from_value::<CacheEntry<T>>(v).ok())
+                .filter(|c| now_secs().saturating_sub(c.saved_at) <= CACHE_TTL_SECS);
+            match entry {
+                Some(c) => Ok(Cached { data: c.value, cached: true, saved_at: c.saved_at }),
+                None => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// 退出登录时清掉上一个账号的推广数据
+pub fn clear_cache(host: &Host) -> Result<(), AppError> {
+    for key in host.store().keys() {
+        if key.starts_with(CACHE_PREFIX) {
+            host.store().delete(&key)?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn invite_info(api: &ApiClient, host: &Host) -> Result<Cached<InviteInfoVO>, AppError> {
+    with_cache(host, "invite", async {
+        let mut info: InviteInfoVO = api
+            .auth_get(host, "/api/v1/referral/invite")
+            .await?
+            .ok_or_else(|| AppError::ApiError("No data".to_string()))?;
+        if info.invite_link.is_none() {
+            info.invite_link = Some(format!("{}{}", INVITE_LINK_BASE, info.invite_code));
+        }
+        Ok(info)
+    })
+    .await
+}
+
+pub async fn invited_users(
+    api: &ApiClient,
+    host: &Host,
+    page: i64,
+    size: i64,
+) -> Result<Cached<PageVO<InvitedUserVO>>, AppError> {
+    let page = page.max(1);
+    let size = size.clamp(1, 100);
+    let key = format!("invited:{}:{}", page, size);
+    with_cache(host, &key, async {
+        let path = format!("/api/v1/referral/invitees?page={}&size={}", page, size);
+        api.auth_get(host, &path)
+            .await?
+            .ok_or_else(|| AppError::ApiError("No data".to_string()))
+    })
+    .await
+}
+
+pub async fn earnings(api: &ApiClient, host: &Host) -> Result<Cached<ReferralEarningsVO>, AppError> {
+    with_cache(host, "earnings", async {
+        Ok(api.auth_get(host, "/api/v1/referral/earnings").await?.unwrap_or_default())
+    })
+    .await
+}
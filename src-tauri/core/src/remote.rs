@@ -0,0 +1,391 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, Instant};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use crate::api::Api;
+use crate::host::Host;
+use crate::miner::{MinerStats, MiningManager};
+use crate::device_id::get_cloud_device_id;
+use crate::device_reg::{recover_unknown_device, MAX_REREGISTRATIONS};
+use crate::error::AppError;
+use crate::i18n::notify_error;
+use crate::heartbeat::{apply_command, HeartbeatCommand};
+
+// 保活与重连参数
+const PING_INTERVAL_SECS: u64 = 20;
+const PONG_TIMEOUT_SECS: u64 = 60;
+const STATS_INTERVAL_SECS: u64 = 5;
+const MAX_RECONNECT_SECS: u64 = 120;
+
+/// 服务端 -> 客户端
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ServerMsg {
+    Command { id: String, command: HeartbeatCommand },
+    #[serde(other)]
+    Unknown,
+}
+
+/// 客户端 -> 服务端
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ClientMsg<'a> {
+    Ack { id: &'a str, ok: bool, error: Option<AppError> },
+    Stats { data: MinerStats },
+}
+
+/// 启动 WebSocket 远程控制通道：断线自动重连（指数退避），
+/// 收到的指令交给后台任务按序通过 MiningManager 执行，完成后逐条回 ack，同时定期推送 MinerStats。
+pub async fn spawn_remote_channel(api: Api, host: Host, miner: Arc<MiningManager>, mut device_id: String) {
+    tokio::spawn(async move {
+        let mut backoff = 1u64;
+        let mut refreshed = false;
+        let mut recoveries = 0u32;
+        loop {
+            // 重连时以当前账号的 deviceId 为准（切换账号后会变化）
+            if let Some(id) = get_cloud_device_id(&host).await {
+                device_id = id;
+            }
+            // 刚换过 token 仍然 401 时不再立即重试，避免与服务端空转
+            let just_refreshed = std::mem::take(&mut refreshed);
+            let started = Instant::now();
+            match run_session(&api, &host, &miner, &device_id).await {
+                Ok(()) => log::info!("remote channel closed by server"),
+                // 握手 401：access token 已过期，用 refresh token 换新后立即重连（每次连接都重新读取 token）
+                Err(e) if is_status(&e, StatusCode::UNAUTHORIZED) && !just_refreshed => {
+                    match api.refresh_session(&host).await {
+                        Ok(true) => {
+                            refreshed = true;
+                            continue;
+                        }
+                        Ok(false) => log::warn!("remote channel: session expired"),
+                        Err(e) => log::warn!("remote channel: token refresh failed: {}", e),
+                    }
+                    notify_error(&host, "remote:err", &AppError::from(e));
+                }
+                // 握手 404：服务端已不认识本设备，重新注册后（照常退避）用新 id 重连
+                Err(e) if is_status(&e, StatusCode::NOT_FOUND) && recoveries < MAX_REREGISTRATIONS => {
+                    recoveries += 1;
+                    match recover_unknown_device(&api, &host, &device_id).await {
+                        Ok(id) => device_id = id,
+                        Err(e) => log::warn!("remote channel: re-register failed: {}", e),
+                    }
+                }
+                Err(e) => {
+                    log::warn!("remote channel error: {}", e);
+                    notify_error(&host, "remote:err", &AppError::from(e));
+                }
+            }
+            host.emit("remote:disconnected", ());
+            // 连接稳定过一段时间就重置退避
+            if started.elapsed() > Duration::from_secs(MAX_RECONNECT_SECS) {
+                backoff = 1;
+                recoveries = 0;
+            }
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_SECS);
+        }
+    });
+}
+
+/// 握手阶段服务端返回的 HTTP 状态
+fn is_status(err: &anyhow::Error, status: StatusCode) -> bool {
+    matches!(err.downcast_ref::<WsError>(), Some(WsError::Http(resp)) if resp.status() == status)
+}
+
+fn ws_url(base: &str, device_id: &str) -> String {
+    let base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        base.to_string()
+    };
+    format!("{}/api/v1/devices/{}/ws", base, device_id)
+}
+
+async fn run_session(api: &Api, host: &Host, miner: &Arc<MiningManager>, device_id: &str) -> anyhow::Result<()> {
+    let token = Api::access_token(host)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("not logged in"))?;
+    let mut req = ws_url(&api.base, device_id).into_client_request()?;
+    req.headers_mut().insert(AUTHORIZATION, format!("Bearer {}", token).parse()?);
+    let (ws, _) = tokio_tungstenite::connect_async(req).await?;
+    let (mut tx, mut rx) = ws.split();
+    host.emit("remote:connected", ());
+
+    // 启停 XMRig、调用其 HTTP API 都可能较慢，放到单独的任务里按收到的顺序执行，
+    // 不阻塞 ping/pong 与状态推送；会话结束时 commands 被丢弃，任务执行完手头的指令后退出
+    let (commands, mut pending) = mpsc::unbounded_channel::<(String, HeartbeatCommand)>();
+    let (done, mut acks) = mpsc::unbounded_channel::<(String, Result<(), AppError>)>();
+    let (worker_host, worker_miner) = (host.clone(), miner.clone());
+    tokio::spawn(async move {
+        while let Some((id, command)) = pending.recv().await {
+            let result = apply_command(&worker_host, &worker_miner, command).await;
+            if done.send((id, result)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut ping = interval(Duration::from_secs(PING_INTERVAL_SECS));
+    let mut stats = interval(Duration::from_secs(STATS_INTERVAL_SECS));
+    let mut last_pong = Instant::now();
+    loop {
+        tokio::select! {
+            msg = rx.next() => {
+                let msg = match msg {
+                    Some(m) => m?,
+                    None => return Ok(()),
+                };
+                match msg {
+                    Message::Text(text) => {
+                        let (id, command) = match serde_json::from_str(&text) {
+                            Ok(ServerMsg::Command { id, command }) => (id, command),
+                            Ok(ServerMsg::Unknown) => continue,
+                            Err(e) => {
+                                log::warn!("remote channel: bad message: {}", e);
+                                continue;
+                            }
+                        };
+                        let _ = commands.send((id, command));
+                    }
+                    Message::Pong(_) => last_pong = Instant::now(),
+                    Message::Close(_) => return Ok(()),
+                    // Ping 由 tungstenite 自动回复 Pong
+                    _ => {}
+                }
+            }
+            Some((id, result)) = acks.recv() => {
+                let ack = ClientMsg::Ack { id: &id, ok: result.is_ok(), error: result.err() };
+                tx.send(Message::Text(serde_json::to_string(&ack)?)).await?;
+            }
+            _ = ping.tick() => {
+                if last_pong.elapsed() > Duration::from_secs(PONG_TIMEOUT_SECS) {
+                    anyhow::bail!("pong timeout");
+                }
+                tx.send(Message::Ping(Vec::new())).await?;
+            }
+            _ = stats.tick() => {
+                let data = miner.stats().await;
+                tx.send(Message::Text(serde_json::to_string(&ClientMsg::Stats { data })?)).await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, Events, Handler, Request, Response};
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request as WsRequest, Response as WsResponse};
+    use tokio_tungstenite::WebSocketStream;
+
+    type Ws = WebSocketStream<TcpStream>;
+
+    /// 本地服务端：WebSocket 握手只接受 `Bearer <valid>`，否则回 401；
+    /// 其余 HTTP 请求（token 刷新）交给 handler。接受的连接连同请求路径交给测试
+    struct Server {
+        base: String,
+        conns: mpsc::UnboundedReceiver<(String, Ws)>,
+        http: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl Server {
+        // 握手回调的错误类型由 tungstenite 规定
+        #[allow(clippy::result_large_err)]
+        async fn start(valid: &'static str, handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let base = format!("http://{}", listener.local_addr().unwrap());
+            let (tx, conns) = mpsc::unbounded_channel();
+            let http = Arc::new(Mutex::new(Vec::new()));
+            let handler: Handler = Arc::new(handler);
+            let log = http.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    if !is_upgrade(&stream).await {
+                        testutil::serve(stream, &handler, &log).await;
+                        continue;
+                    }
+                    let mut path = String::new();
+                    let accepted = tokio_tungstenite::accept_hdr_async(stream, |req: &WsRequest, resp: WsResponse| {
+                        path = req.uri().path().to_string();
+                        let auth = req.headers().get(AUTHORIZATION).and_then(|v| v.to_str().ok());
+                        if auth == Some(format!("Bearer {}", valid).as_str()) {
+                            Ok(resp)
+                        } else {
+                            let mut err = ErrorResponse::new(None);
+                            *err.status_mut() = StatusCode::UNAUTHORIZED;
+                            Err(err)
+                        }
+                    })
+                    .await;
+                    if let Ok(ws) = accepted {
+                        let _ = tx.send((path, ws));
+                    }
+                }
+            });
+            Self { base, conns, http }
+        }
+
+        async fn accept(&mut self) -> (String, Ws) {
+            tokio::time::timeout(Duration::from_secs(10), self.conns.recv())
+                .await
+                .expect("client did not connect")
+                .unwrap()
+        }
+    }
+
+    async fn is_upgrade(stream: &TcpStream) -> bool {
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = stream.peek(&mut buf).await.unwrap_or(0);
+            let head = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+            if n == 0 || n == buf.len() || head.contains("\r\n\r\n") {
+                return head.contains("upgrade: websocket");
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    fn setup(base: &str, access: &str) -> (Api, Host, Arc<Events>) {
+        let (host, events) = testutil::host();
+        host.store().set("device_id", json!("dev-1")).unwrap();
+        host.store().set("access_token", json!(access)).unwrap();
+        host.store().set("refresh_token", json!("r1")).unwrap();
+        (Api::new(base.to_string()), host, events)
+    }
+
+    async fn next_ack(ws: &mut Ws) -> Value {
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(10), ws.next()).await.unwrap().unwrap().unwrap();
+            if let Message::Text(text) = msg {
+                let v: Value = serde_json::from_str(&text).unwrap();
+                if v["type"] == "ack" {
+                    return v;
+                }
+            }
+        }
+    }
+
+    async fn wait_until(cond: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if cond() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    fn command(id: &str, kind: &str) -> Message {
+        Message::Text(json!({ "type": "command", "id": id, "command": { "type": kind } }).to_string())
+    }
+
+    #[tokio::test]
+    async fn dispatches_commands_and_acks() {
+        let mut srv = Server::start("good", |_| Response::json(404, json!({}))).await;
+        let (api, host, events) = setup(&srv.base, "good");
+        spawn_remote_channel(api, host, Arc::new(MiningManager::default()), String::new()).await;
+
+        let (path, mut ws) = srv.accept().await;
+        assert_eq!(path, "/api/v1/devices/dev-1/ws");
+        wait_until(|| events.named("remote:connected").len() == 1).await;
+
+        ws.send(command("c1", "STOP")).await.unwrap();
+        assert_eq!(next_ack(&mut ws).await, json!({ "type": "ack", "id": "c1", "ok": true, "error": null }));
+
+        // 未运行时暂停失败：ack 带上错误
+        ws.send(command("c2", "PAUSE")).await.unwrap();
+        let ack = next_ack(&mut ws).await;
+        assert_eq!(ack["id"], "c2");
+        assert_eq!(ack["ok"], false);
+        assert!(!ack["error"].is_null());
+    }
+
+    #[tokio::test]
+    async fn commands_run_in_order_off_the_socket_loop() {
+        let mut srv = Server::start("good", |_| Response::json(404, json!({}))).await;
+        let (api, host, _) = setup(&srv.base, "good");
+        spawn_remote_channel(api, host, Arc::new(MiningManager::default()), String::new()).await;
+        let (_, mut ws) = srv.accept().await;
+
+        // 连发几条指令后立刻 ping：pong 不必等指令执行完，ack 按指令顺序返回
+        for id in ["c1", "c2", "c3"] {
+            ws.send(command(id, "STOP")).await.unwrap();
+        }
+        ws.send(Message::Ping(b"p".to_vec())).await.unwrap();
+        let mut ids = Vec::new();
+        let mut ponged = false;
+        while ids.len() < 3 || !ponged {
+            match tokio::time::timeout(Duration::from_secs(10), ws.next()).await.unwrap().unwrap().unwrap() {
+                Message::Pong(_) => ponged = true,
+                Message::Text(text) => {
+                    let v: Value = serde_json::from_str(&text).unwrap();
+                    if v["type"] == "ack" {
+                        ids.push(v["id"].as_str().unwrap().to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(ids, vec!["c1", "c2", "c3"]);
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_server_drops() {
+        let mut srv = Server::start("good", |_| Response::json(404, json!({}))).await;
+        let (api, host, events) = setup(&srv.base, "good");
+        spawn_remote_channel(api, host.clone(), Arc::new(MiningManager::default()), String::new()).await;
+
+        let (_, ws) = srv.accept().await;
+        // 断线期间换了设备：重连使用 store 中的新 id
+        host.store().set("device_id", json!("dev-2")).unwrap();
+        drop(ws);
+
+        let (path, _ws) = srv.accept().await;
+        assert_eq!(path, "/api/v1/devices/dev-2/ws");
+        assert_eq!(events.named("remote:disconnected").len(), 1);
+        wait_until(|| events.named("remote:connected").len() == 2).await;
+    }
+
+    #[tokio::test]
+    async fn refreshes_token_after_401_handshake() {
+        let mut srv = Server::start("fresh", |req| {
+            if req.path == "/api/v1/auth/refresh" && req.json()["refreshToken"] == "r1" {
+                Response::json(200, json!({ "code": 0, "data": { "accessToken": "fresh", "refreshToken": "r2" } }))
+            } else {
+                Response::json(401, json!({}))
+            }
+        })
+        .await;
+        let (api, host, _) = setup(&srv.base, "stale");
+        spawn_remote_channel(api, host.clone(), Arc::new(MiningManager::default()), String::new()).await;
+
+        let (path, _ws) = srv.accept().await;
+        assert_eq!(path, "/api/v1/devices/dev-1/ws");
+        assert_eq!(host.get_str("access_token").as_deref(), Some("fresh"));
+        assert_eq!(host.get_str("refresh_token").as_deref(), Some("r2"));
+        assert_eq!(srv.http.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_refresh_backs_off() {
+        let srv = Server::start("fresh", |_| Response::json(401, json!({}))).await;
+        let (api, host, events) = setup(&srv.base, "stale");
+        spawn_remote_channel(api, host, Arc::new(MiningManager::default()), String::new()).await;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(srv.http.lock().unwrap().len(), 1);
+        assert_eq!(events.named("remote:err").len(), 1);
+        assert!(events.named("remote:connected").is_empty());
+    }
+}
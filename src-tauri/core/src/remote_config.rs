@@ -0,0 +1,370 @@
+use anyhow::{anyhow, bail, Context};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::time::Duration;
+use crate::api::Api;
+use crate::heartbeat::{MAX_INTERVAL_SECS, MIN_INTERVAL_SECS};
+use crate::host::Host;
+use crate::miner::MiningManager;
+
+// 服务端下发的远程配置与功能开关（矿池、心跳周期、资源上限等不再写死在二进制里）。
+// 文档格式：{"payload": base64(JSON), "signature": base64(ed25519(payload 原始字节))}。
+// 验签并通过 schema 校验后才生效；最近一份有效文档原样缓存在 store（即磁盘），
+// 启动时先用缓存，离线也能沿用。未知字段一律忽略，保证旧客户端能读新文档。
+
+const KEY_REMOTE_CONFIG: &str = "remote_config";
+// 服务端配置签名私钥对应的公钥（hex），私钥只在服务端的配置发布流程中。
+// 发布构建必须在编译时通过 HASHTREASURE_CONFIG_PUBKEY 注入，缺失时编译失败（见 README 的 Release builds 一节）；
+// 调试构建未注入时没有可信公钥，远程配置一律不生效（沿用内置默认值）
+#[cfg(all(not(debug_assertions), not(test)))]
+const CONFIG_PUBLIC_KEY: Option<&str> = Some(env!(
+    "HASHTREASURE_CONFIG_PUBKEY",
+    "release builds need HASHTREASURE_CONFIG_PUBKEY (hex ed25519 public key for signed remote config); see README \"Release builds\""
+));
+#[cfg(all(debug_assertions, not(test)))]
+const CONFIG_PUBLIC_KEY: Option<&str> = option_env!("HASHTREASURE_CONFIG_PUBKEY");
+// 测试私钥为 [7u8; 32]，见 testutil::config_key
+#[cfg(test)]
+const CONFIG_PUBLIC_KEY: Option<&str> = Some("ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c");
+const REFRESH_SECS: u64 = 15 * 60;
+const RETRY_SECS: u64 = 60;
+
+/// 服务端返回的签名文档，原样缓存
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SignedConfig {
+    pub payload: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfig {
+    /// 单调递增；版本更低的文档视为回滚，不予应用
+    pub version: u64,
+    #[serde(default)]
+    pub pool: Option<PoolConfig>,
+    #[serde(default)]
+    pub heartbeat_interval_sec: Option<u64>,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+}
+
+/// 默认矿池；服务端为设备签发的挖矿凭证优先于这里
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolConfig {
+    pub url: String,
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Limits {
+    /// XMRig 线程数上限，对所有档位生效
+    #[serde(default)]
+    pub max_cpu_threads: Option<u16>,
+}
+
+impl RemoteConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.version == 0 {
+            bail!("version must be positive");
+        }
+        if let Some(pool) = &self.pool {
+            let port = pool.url.rsplit_once(':').map(|(host, port)| (host, port.parse::<u16>()));
+            if !matches!(port, Some((host, Ok(_))) if !host.is_empty()) {
+                bail!("pool.url must be host:port");
+            }
+            if pool.user.as_deref().is_some_and(|u| u.trim().is_empty()) {
+                bail!("pool.user is empty");
+            }
+        }
+        if let Some(secs) = self.heartbeat_interval_sec {
+            if !(MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&secs) {
+                bail!("heartbeatIntervalSec out of range");
+            }
+        }
+        if self.limits.max_cpu_threads == Some(0) {
+            bail!("limits.maxCpuThreads must be positive");
+        }
+        Ok(())
+    }
+
+    // 变化后需要重启 XMRig 才能生效的部分
+    fn mining_changed(&self, other: &RemoteConfig) -> bool {
+        self.pool != other.pool || self.limits != other.limits
+    }
+}
+
+fn public_key() -> anyhow::Result<VerifyingKey> {
+    let hex_key = CONFIG_PUBLIC_KEY.ok_or_else(|| anyhow!("no config public key in this build"))?;
+    let key: [u8; 32] = hex::decode(hex_key)?
+        .try_into()
+        .map_err(|_| anyhow!("config public key must be 32 bytes"))?;
+    Ok(VerifyingKey::from_bytes(&key)?)
+}
+
+/// 验签并解析、校验文档
+fn verify(signed: &SignedConfig) -> anyhow::Result<RemoteConfig> {
+    let key = public_key()?;
+    let payload = B64.decode(&signed.payload).context("payload is not base64")?;
+    let signature = Signature::from_slice(&B64.decode(&signed.signature).context("signature is not base64")?)?;
+    key.verify(&payload, &signature).context("bad config signature")?;
+    let config: RemoteConfig = serde_json::from_slice(&payload)?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// 当前生效的配置（store 中的缓存，读取时重新验签，防止被本地篡改）
+pub fn current(host: &Host) -> Option<RemoteConfig> {
+    let signed: SignedConfig = serde_json::from_value(host.store().get(KEY_REMOTE_CONFIG)?).ok()?;
+    match verify(&signed) {
+        Ok(c) => Some(c),
+        Err(e) => {
+            log::warn!("cached remote config rejected: {}", e);
+            None
+        }
+    }
+}
+
+/// 功能开关，未下发的一律视为关闭
+pub fn feature(host: &Host, name: &str) -> bool {
+    current(host).and_then(|c| c.features.get(name).copied()).unwrap_or(false)
+}
+
+async fn fetch(api: &Api) -> anyhow::Result<SignedConfig> {
+    let url = format!("{}/api/v1/client/config", api.base);
+    let resp = api.client.get(url).send().await?;
+    api.observe_version(resp.headers());
+    if !resp.status().is_success() {
+        bail!("remote config http {}", resp.status());
+    }
+    Ok(resp.json().await?)
+}
+
+/// 拉取一次并应用；返回是否有变化
+pub async fn refresh(api: &Api, host: &Host, miner: &MiningManager) -> anyhow::Result<bool> {
+    let signed = fetch(api).await?;
+    let next = verify(&signed)?;
+    let prev = current(host);
+    if let Some(prev) = &prev {
+        if next.version < prev.version {
+            log::warn!("ignoring remote config v{} older than v{}", next.version, prev.version);
+            return Ok(false);
+        }
+        if *prev == next {
+            return Ok(false);
+        }
+    }
+    host.store().set(KEY_REMOTE_CONFIG, serde_json::to_value(&signed)?)?;
+    log::info!("remote config v{} applied", next.version);
+    host.emit("config:updated", &next);
+    // 心跳每轮读取 current()，无需通知；矿池/上限变化时重启 XMRig 使新配置生效
+    let mining_changed = prev.map(|p| p.mining_changed(&next)).unwrap_or(true);
+    if mining_changed && miner.is_running().await {
+        log::info!("restarting miner for new remote config");
+        miner.stop().await?;
+        miner.start(host.clone()).await?;
+    }
+    Ok(true)
+}
+
+/// 启动时立即拉取一次，之后定期刷新；失败时沿用缓存并稍后重试
+pub async fn spawn_remote_config(api: Api, host: Host, miner: Arc<MiningManager>) {
+    tokio::spawn(async move {
+        loop {
+            let wait = match refresh(&api, &host, &miner).await {
+                Ok(_) => REFRESH_SECS,
+                Err(e) => {
+                    log::warn!("remote config refresh failed: {}", e);
+                    RETRY_SECS
+                }
+            };
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, sign_config as sign, HttpStub, Response};
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    // 桩服务返回 served 中当前的文档，测试中途可替换
+    async fn config_server(first: Value) -> (HttpStub, Arc<Mutex<Value>>) {
+        let served = Arc::new(Mutex::new(first));
+        let doc = served.clone();
+        let srv = HttpStub::start(move |_| Response::json(200, doc.lock().unwrap().clone())).await;
+        (srv, served)
+    }
+
+    fn doc(version: u64, pool_url: &str) -> Value {
+        json!({ "version": version, "pool": { "url": pool_url, "user": "wallet" }, "heartbeatIntervalSec": 60 })
+    }
+
+    #[tokio::test]
+    async fn valid_document_is_applied_and_cached() {
+        let (srv, _) = config_server(sign(&doc(3, "pool.example.com:443"))).await;
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+
+        assert!(refresh(&api, &host, &MiningManager::default()).await.unwrap());
+        assert_eq!(srv.calls(), vec!["GET /api/v1/client/config"]);
+        let applied = current(&host).unwrap();
+        assert_eq!(applied.version, 3);
+        assert_eq!(applied.pool.unwrap().url, "pool.example.com:443");
+        assert_eq!(applied.heartbeat_interval_sec, Some(60));
+        assert!(host.store().get(KEY_REMOTE_CONFIG).is_some());
+        assert_eq!(events.named("config:updated").len(), 1);
+
+        // 同一份文档再次拉取不算变化
+        assert!(!refresh(&api, &host, &MiningManager::default()).await.unwrap());
+        assert_eq!(events.named("config:updated").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn bad_signature_is_rejected_and_cache_kept() {
+        let (srv, served) = config_server(sign(&doc(1, "pool.example.com:443"))).await;
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+        refresh(&api, &host, &MiningManager::default()).await.unwrap();
+
+        // 篡改 payload 但沿用旧签名
+        let mut forged = sign(&doc(2, "evil.example.com:443"));
+        forged["payload"] = json!(B64.encode(doc(2, "evil.example.com:3333").to_string()));
+        *served.lock().unwrap() = forged;
+        assert!(refresh(&api, &host, &MiningManager::default()).await.is_err());
+
+        // 其他密钥签的文档同样被拒
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        let payload = doc(2, "evil.example.com:443").to_string();
+        *served.lock().unwrap() = json!({
+            "payload": B64.encode(&payload),
+            "signature": B64.encode(other.sign(payload.as_bytes()).to_bytes()),
+        });
+        assert!(refresh(&api, &host, &MiningManager::default()).await.is_err());
+
+        let kept = current(&host).unwrap();
+        assert_eq!((kept.version, kept.pool.unwrap().url.as_str()), (1, "pool.example.com:443"));
+        assert_eq!(events.named("config:updated").len(), 1);
+    }
+
+    #[test]
+    fn tampered_cache_is_ignored() {
+        let (host, _) = testutil::host();
+        let mut signed = sign(&doc(1, "pool.example.com:443"));
+        signed["payload"] = json!(B64.encode(doc(1, "evil.example.com:443").to_string()));
+        host.store().set(KEY_REMOTE_CONFIG, signed).unwrap();
+        assert!(current(&host).is_none());
+    }
+
+    #[tokio::test]
+    async fn rollback_is_ignored() {
+        let (srv, served) = config_server(sign(&doc(5, "pool.example.com:443"))).await;
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+        refresh(&api, &host, &MiningManager::default()).await.unwrap();
+
+        *served.lock().unwrap() = sign(&doc(4, "old.example.com:443"));
+        assert!(!refresh(&api, &host, &MiningManager::default()).await.unwrap());
+        assert_eq!(current(&host).unwrap().version, 5);
+        assert_eq!(events.named("config:updated").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_keys_are_accepted() {
+        let mut next = doc(2, "pool.example.com:443");
+        next["futureSetting"] = json!({ "nested": [1, 2, 3] });
+        next["pool"]["tls"] = json!(true);
+        next["features"] = json!({ "newDashboard": true });
+        let (srv, _) = config_server(sign(&next)).await;
+        let (host, _) = testutil::host();
+        let api = Api::new(srv.base.clone());
+
+        assert!(refresh(&api, &host, &MiningManager::default()).await.unwrap());
+        assert_eq!(current(&host).unwrap().version, 2);
+        assert!(feature(&host, "newDashboard"));
+        assert!(!feature(&host, "unknownFlag"));
+    }
+
+    #[test]
+    fn validate_rejects_each_invalid_field() {
+        let base = doc(1, "pool.example.com:443");
+        let invalid: Vec<(&str, Value)> = vec![
+            ("version", json!(0)),
+            ("pool", json!({ "url": "pool.example.com" })),
+            ("pool", json!({ "url": ":443" })),
+            ("pool", json!({ "url": "pool.example.com:70000" })),
+            ("pool", json!({ "url": "pool.example.com:443", "user": "  " })),
+            ("heartbeatIntervalSec", json!(MIN_INTERVAL_SECS - 1)),
+            ("heartbeatIntervalSec", json!(MAX_INTERVAL_SECS + 1)),
+            ("limits", json!({ "maxCpuThreads": 0 })),
+        ];
+        for (field, value) in invalid {
+            let mut bad = base.clone();
+            bad[field] = value.clone();
+            let signed: SignedConfig = serde_json::from_value(sign(&bad)).unwrap();
+            assert!(verify(&signed).is_err(), "{field} = {value} should be rejected");
+        }
+        let signed: SignedConfig = serde_json::from_value(sign(&base)).unwrap();
+        assert!(verify(&signed).is_ok());
+    }
+
+    // 用 sleep 冒充 XMRig，放在临时目录里，测试结束随目录删除
+    #[cfg(unix)]
+    fn install_fake_xmrig() -> testutil::TempDir {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = testutil::TempDir::new("xmrig");
+        let bin = dir.path().join("xmrig");
+        std::fs::write(&bin, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn mining_change_restarts_running_miner() {
+        let dir = install_fake_xmrig();
+        let (srv, served) = config_server(sign(&doc(1, "pool-a.example.com:443"))).await;
+        let (host, events) = testutil::host();
+        host.store().set("device_id", json!("dev-1")).unwrap();
+        let api = Api::new(srv.base.clone());
+        let miner = MiningManager::with_xmrig_dir(dir.path());
+        refresh(&api, &host, &miner).await.unwrap();
+        miner.start(host.clone()).await.unwrap();
+        assert_eq!(events.named("miner:state").len(), 1);
+
+        // 矿池变化：先停后启，新进程使用新矿池
+        *served.lock().unwrap() = sign(&doc(2, "pool-b.example.com:443"));
+        assert!(refresh(&api, &host, &miner).await.unwrap());
+        assert!(miner.is_running().await);
+        assert_eq!(events.named("miner:state").len(), 3);
+        let written: Value = serde_json::from_slice(&std::fs::read(dir.path().join("config.json")).unwrap()).unwrap();
+        assert_eq!(written["pools"][0]["url"], "pool-b.example.com:443");
+
+        // 上限变化同样重启
+        let mut limited = doc(3, "pool-b.example.com:443");
+        limited["limits"] = json!({ "maxCpuThreads": 2 });
+        *served.lock().unwrap() = sign(&limited);
+        assert!(refresh(&api, &host, &miner).await.unwrap());
+        assert_eq!(events.named("miner:state").len(), 5);
+
+        // 仅功能开关变化不重启
+        limited["version"] = json!(4);
+        limited["features"] = json!({ "newDashboard": true });
+        *served.lock().unwrap() = sign(&limited);
+        assert!(refresh(&api, &host, &miner).await.unwrap());
+        assert_eq!(events.named("miner:state").len(), 5);
+
+        miner.stop().await.unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+// 单元测试公用：内存 store、事件记录与本地 HTTP 桩（代替真实后端）
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use ed25519_dalek::{Signer, SigningKey};
+use crate::error::AppError;
+use crate::host::{EventSink, Host, KvStore};
+
+#[derive(Default)]
+pub struct MemStore(Mutex<Map<String, Value>>);
+
+impl KvStore for MemStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+    fn set(&self, key: &str, value: Value) -> Result<(), AppError> {
+        self.0.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+    fn keys(&self) -> Vec<String> {
+        self.0.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// 记录发出的事件，供断言
+#[derive(Default)]
+pub struct Events(Mutex<Vec<(String, Value)>>);
+
+impl EventSink for Events {
+    fn emit(&self, event: &str, payload: Value) {
+        self.0.lock().unwrap().push((event.to_string(), payload));
+    }
+}
+
+impl Events {
+    pub fn named(&self, name: &str) -> Vec<Value> {
+        self.0.lock().unwrap().iter().filter(|(n, _)| n == name).map(|(_, v)| v.clone()).collect()
+    }
+}
+
+pub fn host() -> (Host, Arc<Events>) {
+    let events = Arc::new(Events::default());
+    (Host::new(Arc::new(MemStore::default()), events.clone()), events)
+}
+
+/// 测试用临时目录，drop 时连同内容删除
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("ht-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 测试构建中远程配置的签名私钥（公钥见 remote_config::CONFIG_PUBLIC_KEY）
+pub fn config_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32])
+}
+
+/// 按服务端格式签名一份远程配置
+pub fn sign_config(doc: &Value) -> Value {
+    let payload = doc.to_string();
+    let signature = config_key().sign(payload.as_bytes());
+    serde_json::json!({ "payload": B64.encode(payload), "signature": B64.encode(signature.to_bytes()) })
+}
+
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    pub fn bytes(status: u16, body: &[u8]) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/octet-stream".into())],
+            body: body.to_vec(),
+        }
+    }
+}
+
+pub type Handler = Arc<dyn Fn(&Request) -> Response + Send + Sync>;
+
+/// 本地 HTTP 桩：每个连接处理一个请求后关闭，记录收到的请求
+pub struct HttpStub {
+    pub base: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl HttpStub {
+    pub async fn start(handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Handler = Arc::new(handler);
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (handler, log) = (handler.clone(), log.clone());
+                tokio::spawn(async move { serve(stream, &handler, &log).await });
+            }
+        });
+        Self { base, requests }
+    }
+
+    /// 收到的 "METHOD path" 列表
+    pub fn calls(&self) -> Vec<String> {
+        self.requests.lock().unwrap().iter().map(|r| format!("{} {}", r.method, r.path)).collect()
+    }
+}
+
+/// 读取一个 HTTP 请求、交给 handler 并写回响应
+pub async fn serve(mut stream: TcpStream, handler: &Handler, log: &Mutex<Vec<Request>>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    // 请求行 "METHOD path HTTP/1.1"
+    let mut first = lines.next().unwrap_or_default().split_whitespace();
+    let method = first.next().unwrap_or_default().to_string();
+    let path = first.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    let len = headers.get("content-length").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+    let mut body = buf[head_end..].to_vec();
+    while body.len() < len {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => body.extend_from_slice(&chunk[..n]),
+        }
+    }
+    let req = Request { method, path, body };
+    let resp = handler(&req);
+    log.lock().unwrap().push(req);
+    let mut out = format!("HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n", resp.status, resp.body.len());
+    for (k, v) in &resp.headers {
+        out.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    out.push_str("\r\n");
+    let _ = stream.write_all(out.as_bytes()).await;
+    let _ = stream.write_all(&resp.body).await;
+    let _ = stream.shutdown().await;
+}
@@ -0,0 +1,492 @@
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use crate::api::Api;
+use crate::error::AppError;
+use crate::host::Host;
+use crate::version::CLIENT_VERSION;
+
+// 应用内升级：按本机的更新通道拉取清单，下载当前平台的安装包，
+// 校验 SHA-256 与 ed25519 签名后暂存，用户确认重启时先停止挖矿再启动安装程序。
+// 签名覆盖 版本号 + 平台 + SHA-256（见 signed_payload），旧安装包无法冒充新版本，
+// 也就无法借清单把客户端回滚到旧版本；启动安装程序前对磁盘上的文件重新校验一遍。
+
+const KEY_CHANNEL: &str = "update_channel";
+const KEY_PENDING: &str = "pending_update";
+// 发布签名私钥对应的公钥（hex），私钥只在发布流水线中。
+// 发布构建必须在编译时通过 HASHTREASURE_UPDATE_PUBKEY 注入，缺失时编译失败（见 README 的 Release builds 一节）；
+// 调试构建未注入时没有可信公钥，任何升级包都无法通过校验
+#[cfg(all(not(debug_assertions), not(test)))]
+const UPDATE_PUBLIC_KEY: Option<&str> = Some(env!(
+    "HASHTREASURE_UPDATE_PUBKEY",
+    "release builds need HASHTREASURE_UPDATE_PUBKEY (hex ed25519 public key for signed update packages); see README \"Release builds\""
+));
+#[cfg(all(debug_assertions, not(test)))]
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("HASHTREASURE_UPDATE_PUBKEY");
+// 测试私钥为 [8u8; 32]，见 tests::signing_key
+#[cfg(test)]
+const UPDATE_PUBLIC_KEY: Option<&str> = Some("1398f62c6d1a457c51ba6a4b5f3dbd2f69fca93216218dc8997e416bd17d93ca");
+// 安装包体积大，单独放宽超时
+const DOWNLOAD_TIMEOUT_SECS: u64 = 30 * 60;
+// 进度事件节流
+const PROGRESS_INTERVAL_MS: u128 = 250;
+
+/// 更新通道，按设备保存（不随账号切换）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// 服务端更新清单；platforms 的 key 形如 "windows-x86_64"
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+    #[serde(default)]
+    platforms: BTreeMap<String, PlatformPackage>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformPackage {
+    pub url: String,
+    /// 安装包 SHA-256（hex）
+    pub sha256: String,
+    /// ed25519 签名（base64），签名内容见 signed_payload
+    pub signature: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// 可用的新版本
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    pub package: PlatformPackage,
+}
+
+/// 已下载并校验通过、等待重启安装的版本
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingUpdate {
+    pub version: String,
+    pub path: PathBuf,
+    // 启动安装程序前据此重新校验文件；旧版本暂存的记录缺这两项，校验失败后需重新下载
+    #[serde(default)]
+    pub sha256: String,
+    #[serde(default)]
+    pub signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+fn fail(detail: impl Into<String>) -> AppError {
+    AppError::UpdateFailed(detail.into())
+}
+
+/// 当前平台在清单中的 key
+pub fn platform() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+pub fn channel(host: &Host) -> UpdateChannel {
+    host.store()
+        .get(KEY_CHANNEL)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_channel(host: &Host, channel: UpdateChannel) -> Result<(), AppError> {
+    host.store().set(KEY_CHANNEL, serde_json::to_value(channel)?)
+}
+
+pub fn pending(host: &Host) -> Option<PendingUpdate> {
+    host.store().get(KEY_PENDING).and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// 启动时调用：暂存的版本已不高于当前版本（已安装或被更高版本取代）时清理掉
+pub fn clear_installed(host: &Host) {
+    let Some(p) = pending(host) else { return };
+    if !is_newer(&p.version) {
+        log::info!("update {} installed, cleaning up", p.version);
+        let _ = std::fs::remove_file(&p.path);
+        let _ = host.store().delete(KEY_PENDING);
+    }
+}
+
+fn is_newer(candidate: &str) -> bool {
+    match (Version::parse(candidate), Version::parse(CLIENT_VERSION)) {
+        (Ok(candidate), Ok(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+/// 查询本机通道上是否有新版本；没有或清单里没有当前平台时返回 None
+pub async fn check(api: &Api, host: &Host) -> Result<Option<UpdateInfo>, AppError> {
+    let channel = channel(host);
+    let url = format!("{}/api/v1/client/updates/{}/manifest.json", api.base, channel.as_str());
+    let resp = api.client.get(url).query(&[("platform", platform().as_str()), ("current", CLIENT_VERSION)]).send().await?;
+    api.observe_version(resp.headers());
+    if !resp.status().is_success() {
+        return Err(AppError::from_api_code(resp.status().as_u16() as i32, "update manifest unavailable"));
+    }
+    let mut manifest: Manifest = resp.json().await?;
+    if !is_newer(&manifest.version) {
+        return Ok(None);
+    }
+    let Some(package) = manifest.platforms.remove(&platform()) else {
+        log::info!("update {} has no package for {}", manifest.version, platform());
+        return Ok(None);
+    };
+    verify_signature(&manifest.version, &package.sha256, &package.signature)?;
+    Ok(Some(UpdateInfo {
+        version: manifest.version,
+        current_version: CLIENT_VERSION.to_string(),
+        channel,
+        notes: manifest.notes,
+        pub_date: manifest.pub_date,
+        package,
+    }))
+}
+
+/// 下载安装包到 dir，边写盘边计算 SHA-256 并推送 update:progress（不在内存中缓存整个安装包）；
+/// 哈希与签名都通过后记为待安装版本，否则删除文件
+pub async fn download(api: &Api, host: &Host, info: &UpdateInfo, dir: &Path) -> Result<PendingUpdate, AppError> {
+    // info 来自前端，先校验签名再下载
+    if !is_newer(&info.version) {
+        return Err(fail(format!("{} is not newer than {}", info.version, CLIENT_VERSION)));
+    }
+    verify_signature(&info.version, &info.package.sha256, &info.package.signature)?;
+    tokio::fs::create_dir_all(dir).await?;
+    let file_name = info
+        .package
+        .url
+        .rsplit('/')
+        .next()
+        .and_then(|n| n.split('?').next())
+        .filter(|n| !n.is_empty() && !n.contains(".."))
+        .ok_or_else(|| fail("bad package url"))?;
+    let path = dir.join(file_name);
+    let mut resp = api
+        .client
+        .get(&info.package.url)
+        .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT_SECS))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(fail(format!("download http {}", resp.status())));
+    }
+    let total = resp.content_length().or(info.package.size);
+    let mut file = tokio::fs::File::create(&path).await?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    let mut last_emit = Instant::now();
+    while let Some(chunk) = resp.chunk().await? {
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        file.write_all(&chunk).await?;
+        if last_emit.elapsed().as_millis() >= PROGRESS_INTERVAL_MS {
+            host.emit("update:progress", Progress { downloaded, total });
+            last_emit = Instant::now();
+        }
+    }
+    file.flush().await?;
+    drop(file);
+    host.emit("update:progress", Progress { downloaded, total });
+
+    if let Err(e) = check_sha256(&hex::encode(hasher.finalize()), &info.package.sha256) {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(e);
+    }
+    let pending = PendingUpdate {
+        version: info.version.clone(),
+        path,
+        sha256: info.package.sha256.trim().to_ascii_lowercase(),
+        signature: info.package.signature.clone(),
+    };
+    host.store().set(KEY_PENDING, serde_json::to_value(&pending)?)?;
+    log::info!("update {} downloaded and verified", pending.version);
+    host.emit("update:ready", &pending);
+    Ok(pending)
+}
+
+/// 发布流水线签名的内容："hashtreasure-update:<版本>:<平台>:<小写 sha256>"
+fn signed_payload(version: &str, platform: &str, sha256: &str) -> String {
+    format!("hashtreasure-update:{}:{}:{}", version, platform, sha256.trim().to_ascii_lowercase())
+}
+
+fn verify_signature(version: &str, sha256: &str, signature: &str) -> Result<(), AppError> {
+    let key: [u8; 32] = UPDATE_PUBLIC_KEY
+        .and_then(|k| hex::decode(k).ok())
+        .and_then(|k| k.try_into().ok())
+        .ok_or_else(|| fail("no update public key in this build"))?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|_| fail("bad update public key"))?;
+    let signature = B64
+        .decode(signature.trim())
+        .ok()
+        .and_then(|s| Signature::from_slice(&s).ok())
+        .ok_or_else(|| fail("malformed signature"))?;
+    key.verify(signed_payload(version, &platform(), sha256).as_bytes(), &signature)
+        .map_err(|_| fail("signature mismatch"))
+}
+
+fn check_sha256(actual: &str, expected: &str) -> Result<(), AppError> {
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(fail("sha256 mismatch"));
+    }
+    Ok(())
+}
+
+/// 重新计算暂存安装包的 SHA-256 并验签（流式读取），防止下载后被替换
+pub fn verify_pending(pending: &PendingUpdate) -> Result<(), AppError> {
+    let mut file = std::fs::File::open(&pending.path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    check_sha256(&hex::encode(hasher.finalize()), &pending.sha256)?;
+    verify_signature(&pending.version, &pending.sha256, &pending.signature)
+}
+
+/// 启动安装程序（启动前再校验一次文件）。调用方需先停止挖矿，之后退出（或重启）应用：
+/// Windows 运行安装包（静默模式，安装完成后由安装程序重新拉起）；
+/// Linux AppImage 直接替换当前文件，重启即是新版本；其它平台交给系统打开安装包。
+pub fn launch_installer(pending: &PendingUpdate) -> Result<(), AppError> {
+    verify_pending(pending)?;
+    let path = &pending.path;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    log::info!("installing update {} from {}", pending.version, path.display());
+    if cfg!(target_os = "windows") {
+        let mut cmd = if ext == "msi" {
+            let mut c = std::process::Command::new("msiexec");
+            c.arg("/i").arg(path).arg("/passive");
+            c
+        } else {
+            let mut c = std::process::Command::new(path);
+            c.arg("/S");
+            c
+        };
+        cmd.spawn()?;
+        return Ok(());
+    }
+    if let (Some(appimage), "appimage") = (std::env::var_os("APPIMAGE"), ext.as_str()) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        // 先复制到同目录再 rename，避免半个文件
+        let target = PathBuf::from(appimage);
+        let tmp = target.with_extension("new");
+        std::fs::copy(path, &tmp)?;
+        std::fs::rename(&tmp, &target)?;
+        return Ok(());
+    }
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    std::process::Command::new(opener).arg(path).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, HttpStub, Response};
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    const PACKAGE: &[u8] = b"installer bytes";
+    const NEWER: &str = "99.0.0";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[8u8; 32])
+    }
+
+    fn sign_with(key: &SigningKey, version: &str, sha256: &str) -> String {
+        B64.encode(key.sign(signed_payload(version, &platform(), sha256).as_bytes()).to_bytes())
+    }
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        hex::encode(Sha256::digest(bytes))
+    }
+
+    fn manifest(base: &str, version: &str, sha256: &str, signature: &str) -> Value {
+        json!({
+            "version": version,
+            "notes": "fixes",
+            "platforms": {
+                platform(): { "url": format!("{}/files/HashTreasure-setup.exe", base), "sha256": sha256, "signature": signature },
+            },
+        })
+    }
+
+    // 清单在测试中途替换；其它路径一律返回 PACKAGE
+    async fn update_server() -> (HttpStub, Arc<Mutex<Value>>) {
+        let served = Arc::new(Mutex::new(Value::Null));
+        let doc = served.clone();
+        let srv = HttpStub::start(move |req| {
+            if req.path.contains("/manifest.json") {
+                Response::json(200, doc.lock().unwrap().clone())
+            } else {
+                Response::bytes(200, PACKAGE)
+            }
+        })
+        .await;
+        (srv, served)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ht-update-{}-{}", std::process::id(), name))
+    }
+
+    #[tokio::test]
+    async fn valid_update_is_downloaded_and_verified() {
+        let (srv, served) = update_server().await;
+        let sha = sha256_hex(PACKAGE);
+        *served.lock().unwrap() = manifest(&srv.base, NEWER, &sha, &sign_with(&signing_key(), NEWER, &sha));
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+
+        let info = check(&api, &host).await.unwrap().expect("update available");
+        assert_eq!((info.version.as_str(), info.channel), (NEWER, UpdateChannel::Stable));
+        let dir = temp_dir("valid");
+        let staged = download(&api, &host, &info, &dir).await.unwrap();
+        assert_eq!(std::fs::read(&staged.path).unwrap(), PACKAGE);
+        assert_eq!(staged.sha256, sha);
+        assert_eq!(pending(&host).unwrap().version, NEWER);
+        assert_eq!(events.named("update:ready").len(), 1);
+        assert_eq!(events.named("update:progress").last().unwrap()["downloaded"], PACKAGE.len());
+        verify_pending(&staged).unwrap();
+
+        // 下载后被替换的文件不再通过校验
+        std::fs::write(&staged.path, b"replaced").unwrap();
+        assert!(matches!(verify_pending(&staged), Err(AppError::UpdateFailed(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sha256_mismatch_discards_download() {
+        let (srv, served) = update_server().await;
+        // 签名有效，但对应的是另一个文件
+        let sha = sha256_hex(b"other installer");
+        *served.lock().unwrap() = manifest(&srv.base, NEWER, &sha, &sign_with(&signing_key(), NEWER, &sha));
+        let (host, events) = testutil::host();
+        let api = Api::new(srv.base.clone());
+
+        let info = check(&api, &host).await.unwrap().unwrap();
+        let dir = temp_dir("mismatch");
+        let err = download(&api, &host, &info, &dir).await.unwrap_err();
+        assert!(matches!(err, AppError::UpdateFailed(ref d) if d.contains("sha256")));
+        assert!(!dir.join("HashTreasure-setup.exe").exists());
+        assert!(pending(&host).is_none());
+        assert!(events.named("update:ready").is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn bad_signature_is_rejected() {
+        let (srv, served) = update_server().await;
+        let sha = sha256_hex(PACKAGE);
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        *served.lock().unwrap() = manifest(&srv.base, NEWER, &sha, &sign_with(&other, NEWER, &sha));
+        let (host, _) = testutil::host();
+        let api = Api::new(srv.base.clone());
+        assert!(matches!(check(&api, &host).await, Err(AppError::UpdateFailed(_))));
+
+        // 签名绑定版本号：旧版本的签名不能配到新版本号上
+        *served.lock().unwrap() = manifest(&srv.base, NEWER, &sha, &sign_with(&signing_key(), "98.0.0", &sha));
+        assert!(matches!(check(&api, &host).await, Err(AppError::UpdateFailed(_))));
+
+        // 前端传回的 info 被改过版本号同样拒绝，且不会发起下载
+        let signature = sign_with(&signing_key(), NEWER, &sha);
+        *served.lock().unwrap() = manifest(&srv.base, NEWER, &sha, &signature);
+        let mut info = check(&api, &host).await.unwrap().unwrap();
+        info.version = "99.0.1".into();
+        let dir = temp_dir("forged");
+        assert!(download(&api, &host, &info, &dir).await.is_err());
+        assert!(!srv.calls().iter().any(|c| c.contains("/files/")));
+        assert!(pending(&host).is_none());
+    }
+
+    #[tokio::test]
+    async fn no_package_for_this_platform() {
+        let (srv, served) = update_server().await;
+        let sha = sha256_hex(PACKAGE);
+        *served.lock().unwrap() = json!({
+            "version": NEWER,
+            "platforms": {
+                "plan9-mips": { "url": format!("{}/files/setup", srv.base), "sha256": sha, "signature": "" },
+            },
+        });
+        let (host, _) = testutil::host();
+        assert!(check(&Api::new(srv.base.clone()), &host).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn manifest_url_follows_channel() {
+        let (srv, served) = update_server().await;
+        // 不高于当前版本的清单不算更新
+        *served.lock().unwrap() = json!({ "version": CLIENT_VERSION, "platforms": {} });
+        let (host, _) = testutil::host();
+        let api = Api::new(srv.base.clone());
+
+        assert!(check(&api, &host).await.unwrap().is_none());
+        set_channel(&host, UpdateChannel::Beta).unwrap();
+        assert!(check(&api, &host).await.unwrap().is_none());
+        set_channel(&host, UpdateChannel::Stable).unwrap();
+        assert!(check(&api, &host).await.unwrap().is_none());
+
+        let query = format!("?platform={}&current={}", platform(), CLIENT_VERSION);
+        let calls = srv.calls();
+        assert_eq!(
+            calls,
+            vec![
+                format!("GET /api/v1/client/updates/stable/manifest.json{}", query),
+                format!("GET /api/v1/client/updates/beta/manifest.json{}", query),
+                format!("GET /api/v1/client/updates/stable/manifest.json{}", query),
+            ]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use reqwest::header::HeaderMap;
+use semver::Version;
+use serde::Serialize;
+use std::sync::Arc;
+use crate::error::AppError;
+use crate::host::Host;
+
+// 客户端版本门槛：服务端在任意响应上通过响应头（或 ApiResponse 字段）告知最低/推荐版本。
+// 低于最低版本时禁止挖矿并发 app:update-required；低于推荐版本时发 app:update-available。
+// 门槛写入 store，重启后（包括离线）依然生效，直到服务端下发新的值。
+
+/// 工作区内各 crate 同步发版，core 的版本即客户端版本
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// 客户端与服务端之间的接口协议版本，不兼容变更时递增
+pub const PROTOCOL_VERSION: u32 = 1;
+
+pub const HEADER_CLIENT_VERSION: &str = "x-client-version";
+pub const HEADER_PROTOCOL_VERSION: &str = "x-protocol-version";
+const HEADER_MIN_VERSION: &str = "x-min-client-version";
+const HEADER_RECOMMENDED_VERSION: &str = "x-recommended-client-version";
+
+const KEY_MIN_VERSION: &str = "min_client_version";
+const KEY_RECOMMENDED_VERSION: &str = "recommended_client_version";
+
+/// 最低版本提到当前版本之上时的处理（停止正在运行的挖矿）
+pub type OnUnsupported = Arc<dyn Fn() + Send + Sync>;
+
+/// 版本门槛的去处：由 Api 携带（见 Api::with_version_gate），
+/// 收到的门槛写入该 host 的 store 并通过它发事件
+#[derive(Clone)]
+pub struct VersionGate {
+    host: Host,
+    on_unsupported: Option<OnUnsupported>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub app: String,
+    pub xmrig: Option<String>,
+    pub protocol: u32,
+    pub minimum: Option<String>,
+    pub recommended: Option<String>,
+    pub update_required: bool,
+    pub update_available: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateEvent<'a> {
+    current: &'a str,
+    required: &'a str,
+}
+
+// 解析失败的版本号一律忽略，避免服务端配错导致全部客户端停挖
+fn below(threshold: &str) -> bool {
+    let threshold = threshold.trim();
+    let threshold = threshold.strip_prefix('v').unwrap_or(threshold);
+    match (Version::parse(CLIENT_VERSION), Version::parse(threshold)) {
+        (Ok(current), Ok(threshold)) => current < threshold,
+        _ => false,
+    }
+}
+
+impl VersionGate {
+    pub fn new(host: Host) -> Self {
+        Self { host, on_unsupported: None }
+    }
+
+    /// 登记最低版本高于当前版本时的处理。
+    /// 回调在收到响应的任务上同步执行，需要等待的操作请自行 spawn
+    pub fn on_unsupported(mut self, stop: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_unsupported = Some(Arc::new(stop));
+        self
+    }
+
+    /// 检查响应头中的版本门槛
+    pub fn observe(&self, headers: &HeaderMap) {
+        let get = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+        self.observe_fields(get(HEADER_MIN_VERSION).as_deref(), get(HEADER_RECOMMENDED_VERSION).as_deref());
+    }
+
+    /// 检查 ApiResponse 中的 minClientVersion / recommendedClientVersion
+    pub fn observe_fields(&self, minimum: Option<&str>, recommended: Option<&str>) {
+        if let Some(v) = minimum {
+            self.record(KEY_MIN_VERSION, v, "app:update-required");
+        }
+        if let Some(v) = recommended {
+            self.record(KEY_RECOMMENDED_VERSION, v, "app:update-available");
+        }
+    }
+
+    // 门槛有变化时落盘；当前版本低于新门槛时发一次事件（相同门槛不重复提醒）。
+    // 低于最低版本时同时停止挖矿，不等下一轮心跳；回调里不持有任何锁，可以再发请求
+    fn record(&self, key: &str, threshold: &str, event: &str) {
+        let host = &self.host;
+        if host.get_str(key).as_deref() == Some(threshold) {
+            return;
+        }
+        if let Err(e) = host.store().set(key, threshold.into()) {
+            log::warn!("saving {} failed: {}", key, e);
+        }
+        if below(threshold) {
+            log::warn!("client {} is below {} {}", CLIENT_VERSION, key, threshold);
+            if key == KEY_MIN_VERSION {
+                if let Some(stop) = &self.on_unsupported {
+                    stop();
+                }
+            }
+            host.emit(event, UpdateEvent { current: CLIENT_VERSION, required: threshold });
+        }
+    }
+}
+
+/// 低于最低版本时返回 UpdateRequired，挖矿入口据此拒绝启动
+pub fn ensure_supported(host: &Host) -> Result<(), AppError> {
+    match host.get_str(KEY_MIN_VERSION) {
+        Some(min) if below(&min) => Err(AppError::UpdateRequired(min)),
+        _ => Ok(()),
+    }
+}
+
+pub fn info(host: &Host, xmrig: Option<String>) -> VersionInfo {
+    let minimum = host.get_str(KEY_MIN_VERSION);
+    let recommended = host.get_str(KEY_RECOMMENDED_VERSION);
+    VersionInfo {
+        app: CLIENT_VERSION.to_string(),
+        xmrig,
+        protocol: PROTOCOL_VERSION,
+        update_required: minimum.as_deref().is_some_and(below),
+        update_available: recommended.as_deref().is_some_and(below),
+        minimum,
+        recommended,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 以当前版本为基准构造门槛，发版后测试不用跟着改
+    fn next_patch() -> String {
+        let v = Version::parse(CLIENT_VERSION).unwrap();
+        format!("{}.{}.{}", v.major, v.minor, v.patch + 1)
+    }
+
+    #[test]
+    fn below_accepts_v_prefix() {
+        assert!(below(&format!("v{}", next_patch())));
+        assert!(below(&format!(" v{} ", next_patch())));
+        assert!(!below(&format!("v{}", CLIENT_VERSION)));
+        assert!(!below("v0.0.1"));
+    }
+
+    #[test]
+    fn below_ignores_malformed_thresholds() {
+        for bad in ["", "v", "latest", "1.2", "1.2.3.4", "999", "vv999.0.0", "999.0.0 beta"] {
+            assert!(!below(bad), "{bad:?} should be ignored");
+        }
+    }
+
+    #[test]
+    fn below_orders_pre_releases() {
+        // 预发布版本排在对应正式版之前
+        assert!(below(&format!("{}-rc.1", next_patch())));
+        assert!(!below(&format!("{}-beta.1", CLIENT_VERSION)));
+        assert!(!below(&format!("v{}-alpha", CLIENT_VERSION)));
+    }
+
+    #[test]
+    fn raised_minimum_stops_mining() {
+        let stops = Arc::new(AtomicUsize::new(0));
+        let counter = stops.clone();
+        let (host, events) = testutil::host();
+        let gate = VersionGate::new(host.clone()).on_unsupported(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // 推荐版本与不高于当前的最低版本都不停挖
+        gate.observe_fields(Some("0.0.1"), Some(&next_patch()));
+        assert_eq!(stops.load(Ordering::SeqCst), 0);
+        assert_eq!(events.named("app:update-available").len(), 1);
+
+        gate.observe_fields(Some(&next_patch()), None);
+        assert_eq!(stops.load(Ordering::SeqCst), 1);
+        assert_eq!(events.named("app:update-required").len(), 1);
+        assert!(matches!(ensure_supported(&host), Err(AppError::UpdateRequired(_))));
+
+        // 相同门槛不重复处理
+        gate.observe_fields(Some(&next_patch()), None);
+        assert_eq!(stops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn thresholds_stay_with_their_host() {
+        let (a, _) = testutil::host();
+        let (b, _) = testutil::host();
+        VersionGate::new(a.clone()).observe_fields(Some(&next_patch()), None);
+        assert!(ensure_supported(&a).is_err());
+        assert!(ensure_supported(&b).is_ok());
+    }
+
+    #[test]
+    fn callback_may_observe_again() {
+        // 回调里再处理响应（例如停挖时发请求）不会死锁
+        let (host, _) = testutil::host();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = VersionGate::new(host.clone());
+        let counter = calls.clone();
+        let gate = VersionGate::new(host.clone()).on_unsupported(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            inner.observe_fields(Some("999.0.0"), Some("999.0.0"));
+        });
+        gate.observe_fields(Some(&next_patch()), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(host.get_str(KEY_MIN_VERSION).as_deref(), Some("999.0.0"));
+    }
+}
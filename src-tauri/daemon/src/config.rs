@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::miner::MiningProfile;
+
+pub const DEFAULT_CONFIG_PATH: &str = "/etc/hashtreasure/daemon.toml";
+// systemd LoadCredential=daemon.toml:... 把配置复制到 $CREDENTIALS_DIRECTORY 下，仅服务进程（含 DynamicUser）可读
+const CREDENTIAL_NAME: &str = "daemon.toml";
+const DEFAULT_STATE_DIR: &str = "/var/lib/hashtreasure";
+
+/// daemon 配置：先读 TOML 文件，再用 HASHTREASURE_* 环境变量逐项覆盖
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// 后端地址，如 https://api.example.com
+    pub api_base: String,
+    pub email: Option<String>,
+    pub password: Option<String>,
+    /// 管理员签发的注册令牌：首次启动时换取设备级凭证，之后无需账号登录
+    pub enrollment_token: Option<String>,
+    /// 首次注册时使用的设备名，缺省为 "<email>@<hostname>"
+    pub device_name: Option<String>,
+    pub profile: MiningProfile,
+    /// store.json 所在目录
+    pub state_dir: Option<PathBuf>,
+    /// 是否自动开始挖矿（否则等服务端指令）
+    pub autostart: Option<bool>,
+    /// 是否连接 WebSocket 远程控制通道
+    pub remote: Option<bool>,
+    /// env_logger 过滤表达式，如 "info" 或 "info,xmrig=warn"
+    pub log: Option<String>,
+}
+
+impl DaemonConfig {
+    /// 读取配置文件（使用默认路径且文件不存在时视为空配置），然后应用环境变量
+    pub fn load(path: Option<&Path>) -> Result<Self, AppError> {
+        let explicit = path.is_some();
+        let path = path.map(Path::to_path_buf).unwrap_or_else(default_config_path);
+        let path = path.as_path();
+        let mut cfg = match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text)
+                .map_err(|e| AppError::JsonParseError(format!("{}: {}", path.display(), e).into()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => DaemonConfig::default(),
+            Err(e) => return Err(e.into()),
+        };
+        cfg.apply_env()?;
+        if cfg.api_base.trim().is_empty() {
+            return Err(AppError::InvalidInput("api_base"));
+        }
+        Ok(cfg)
+    }
+
+    fn apply_env(&mut self) -> Result<(), AppError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        // 与桌面端 ApiClient 保持一致，也接受 API_BASE_URL
+        if let Some(v) = var("HASHTREASURE_API_BASE").or_else(|| var("API_BASE_URL")) {
+            self.api_base = v;
+        }
+        if let Some(v) = var("HASHTREASURE_EMAIL") {
+            self.email = Some(v);
+        }
+        if let Some(v) = var("HASHTREASURE_PASSWORD") {
+            self.password = Some(v);
+        }
+        if let Some(v) = var("HASHTREASURE_ENROLLMENT_TOKEN") {
+            self.enrollment_token = Some(v);
+        }
+        if let Some(v) = var("HASHTREASURE_DEVICE_NAME") {
+            self.device_name = Some(v);
+        }
+        if let Some(v) = var("HASHTREASURE_PROFILE") {
+            self.profile = serde_json::from_value(serde_json::Value::String(v))
+                .map_err(|_| AppError::InvalidInput("HASHTREASURE_PROFILE"))?;
+        }
+        if let Some(v) = var("HASHTREASURE_STATE_DIR") {
+            self.state_dir = Some(PathBuf::from(v));
+        }
+        if let Some(v) = var("HASHTREASURE_AUTOSTART") {
+            self.autostart = Some(parse_bool(&v).ok_or(AppError::InvalidInput("HASHTREASURE_AUTOSTART"))?);
+        }
+        if let Some(v) = var("HASHTREASURE_REMOTE") {
+            self.remote = Some(parse_bool(&v).ok_or(AppError::InvalidInput("HASHTREASURE_REMOTE"))?);
+        }
+        if let Some(v) = var("HASHTREASURE_LOG") {
+            self.log = Some(v);
+        }
+        Ok(())
+    }
+
+    pub fn state_dir(&self) -> PathBuf {
+        // systemd 的 StateDirectory= 会设置 STATE_DIRECTORY
+        self.state_dir
+            .clone()
+            .or_else(|| std::env::var_os("STATE_DIRECTORY").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_STATE_DIR))
+    }
+}
+
+/// 未指定 --config 时：优先读取 systemd 传入的凭证，其次是 /etc 下的默认路径
+pub fn default_config_path() -> PathBuf {
+    std::env::var_os("CREDENTIALS_DIRECTORY")
+        .map(|dir| PathBuf::from(dir).join(CREDENTIAL_NAME))
+        .filter(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+}
+
+fn parse_bool(v: &str) -> Option<bool> {
+    match v.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_systemd_credential() {
+        let dir = std::env::temp_dir().join(format!("ht-cred-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("CREDENTIALS_DIRECTORY", &dir);
+        // 凭证目录里没有 daemon.toml 时回落到 /etc
+        assert_eq!(default_config_path(), PathBuf::from(DEFAULT_CONFIG_PATH));
+
+        std::fs::write(dir.join(CREDENTIAL_NAME), "api_base = \"https://api.example.com\"\n").unwrap();
+        assert_eq!(default_config_path(), dir.join(CREDENTIAL_NAME));
+        let cfg = DaemonConfig::load(None).unwrap();
+        assert_eq!(cfg.api_base, "https://api.example.com");
+
+        std::env::remove_var("CREDENTIALS_DIRECTORY");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
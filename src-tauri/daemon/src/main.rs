@@ -0,0 +1,266 @@
+// 无界面的 Linux 服务：基于 hashtreasure-core（MiningManager / Api / 设备注册 / 心跳），
+// 配置来自 TOML 文件 + HASHTREASURE_* 环境变量，日志写 stdout（systemd 下即 journald）。
+
+mod config;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use hashtreasure_core::api::{Api, ApiClient, Reauth};
+use hashtreasure_core::device_id::{get_cloud_device_name, set_cloud_device_name};
+use hashtreasure_core::device_reg::{assigned_profile, enroll, ensure_registered, is_enrolled};
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::heartbeat::{sampler, spawn_heartbeat};
+use hashtreasure_core::host::{FileStore, Host, LogSink};
+use hashtreasure_core::miner::MiningManager;
+use hashtreasure_core::models::{mask_email, UserLoginDTO};
+use hashtreasure_core::redact::redact;
+use hashtreasure_core::remote::spawn_remote_channel;
+use hashtreasure_core::remote_config::spawn_remote_config;
+use hashtreasure_core::version;
+use crate::config::{default_config_path, DaemonConfig};
+
+const USAGE: &str = "usage: hashtreasure-daemon [--config <path>] [--enroll <token>]";
+
+struct Args {
+    config: Option<PathBuf>,
+    enroll: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match parse_args() {
+        Ok(a) => a,
+        Err(msg) => {
+            eprintln!("{}\n{}", msg, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mut cfg = match DaemonConfig::load(args.config.as_deref()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("failed to load config: {}", e);
+            std::process::exit(2);
+        }
+    };
+    // 命令行优先于配置文件与环境变量
+    if args.enroll.is_some() {
+        cfg.enrollment_token = args.enroll;
+    }
+    init_logger(cfg.log.as_deref().unwrap_or("info"));
+    if let Err(e) = run(cfg).await {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args {
+        config: std::env::var_os("HASHTREASURE_CONFIG").map(PathBuf::from),
+        enroll: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--config" => {
+                parsed.config = Some(args.next().ok_or("--config needs a path")?.into());
+            }
+            "--enroll" => {
+                parsed.enroll = Some(args.next().ok_or("--enroll needs a token")?);
+            }
+            "-h" | "--help" => {
+                println!("{}\n\ndefault config: {}", USAGE, default_config_path().display());
+                std::process::exit(0);
+            }
+            "-V" | "--version" => {
+                println!("hashtreasure-daemon {}", env!("CARGO_PKG_VERSION"));
+                std::process::exit(0);
+            }
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+// 与桌面端日志插件相同：先脱敏再输出。
+// systemd 下（JOURNAL_STREAM 已设置）用 <N> 前缀标注级别，时间戳交给 journald。
+fn init_logger(filters: &str) {
+    let journald = std::env::var_os("JOURNAL_STREAM").is_some();
+    env_logger::Builder::new()
+        .parse_filters(filters)
+        .target(env_logger::Target::Stdout)
+        .format(move |buf, record| {
+            let message = record.args().to_string();
+            let message = redact(&message);
+            if journald {
+                let prio = match record.level() {
+                    log::Level::Error => 3,
+                    log::Level::Warn => 4,
+                    log::Level::Info => 6,
+                    log::Level::Debug | log::Level::Trace => 7,
+                };
+                writeln!(buf, "<{}>[{}] {}", prio, record.target(), message)
+            } else {
+                writeln!(buf, "{} [{}][{}] {}", buf.timestamp(), record.level(), record.target(), message)
+            }
+        })
+        .init();
+}
+
+async fn run(cfg: DaemonConfig) -> Result<(), AppError> {
+    let state_dir = cfg.state_dir();
+    let store = FileStore::open(state_dir.join("store.json"))?;
+    let host = Host::new(Arc::new(store), Arc::new(LogSink));
+    let miner = Arc::new(MiningManager::default());
+    // 任意响应把最低版本提到当前版本之上时立即停挖
+    let stop_miner = miner.clone();
+    let gate = version::VersionGate::new(host.clone()).on_unsupported(move || {
+        let miner = stop_miner.clone();
+        tokio::spawn(async move {
+            if miner.is_running().await {
+                let _ = miner.stop().await;
+            }
+        });
+    });
+    let client = ApiClient::with_base(cfg.api_base.clone()).with_version_gate(gate.clone());
+    let mut api = client.api().clone();
+    log::info!("hashtreasure-daemon {} starting, state in {}", env!("CARGO_PKG_VERSION"), state_dir.display());
+
+    match login_method(&cfg, is_enrolled(&host), Api::access_token(&host).await.is_some())? {
+        LoginMethod::Password { email, password } => {
+            password_login(&client, &host, email, password).await?;
+            log::info!("logged in as {}", mask_email(email));
+            // 邮箱登录只拿到一个 token，没有 refresh token：过期后用配置的账号重新登录
+            api = api.with_reauth(relogin(&cfg.api_base, &gate, &host, email, password));
+        }
+        LoginMethod::Enroll(token) => {
+            let vo = enroll(&client, &host, token, cfg.device_name.as_deref()).await?;
+            log::info!("enrolled as device {}", vo.device_id);
+        }
+        LoginMethod::Stored => log::info!("using stored credentials"),
+    }
+
+    if let Some(name) = &cfg.device_name {
+        if get_cloud_device_name(&host).await.is_none() {
+            set_cloud_device_name(&host, name).await;
+        }
+    }
+    let username = cfg.email.clone().unwrap_or_else(|| "daemon".to_string());
+    let device_id = ensure_registered(&api, &host, &username).await?;
+    log::info!("registered as device {}", device_id);
+
+    // 先用缓存的远程配置启动，后台拉取到新版本后再热更新
+    spawn_remote_config(api.clone(), host.clone(), miner.clone()).await;
+    // 管理员分配的档位优先于本地配置
+    let profile = assigned_profile(&host).unwrap_or(cfg.profile);
+    log::info!("mining profile: {:?}", profile);
+    miner.set_profile(host.clone(), profile).await?;
+    if cfg.autostart.unwrap_or(true) {
+        match miner.start(host.clone()).await {
+            // 版本过低时不退出（systemd 会反复拉起），继续心跳等待升级
+            Err(AppError::UpdateRequired(min)) => log::error!("not mining: version {} or later required", min),
+            other => other?,
+        }
+    }
+
+    spawn_heartbeat(api.clone(), host.clone(), miner.clone(), device_id.clone(), sampler(miner.clone())).await;
+    if cfg.remote.unwrap_or(true) {
+        spawn_remote_channel(api, host.clone(), miner.clone(), device_id).await;
+    }
+
+    wait_for_shutdown().await?;
+    log::info!("shutting down");
+    if miner.is_running().await {
+        miner.stop().await?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum LoginMethod<'a> {
+    Password { email: &'a str, password: &'a str },
+    Enroll(&'a str),
+    Stored,
+}
+
+// 配了账号就每次启动重新登录（旧 token 可能已过期）；
+// 配了注册令牌且尚未入网则换取设备凭证；否则沿用 store 里的 token
+fn login_method(cfg: &DaemonConfig, enrolled: bool, has_token: bool) -> Result<LoginMethod<'_>, AppError> {
+    match (cfg.email.as_deref(), cfg.password.as_deref(), cfg.enrollment_token.as_deref()) {
+        (Some(email), Some(password), _) => Ok(LoginMethod::Password { email, password }),
+        (_, _, Some(token)) if !enrolled => Ok(LoginMethod::Enroll(token)),
+        _ if has_token => Ok(LoginMethod::Stored),
+        _ => Err(AppError::InvalidInput("email/password or enrollment_token")),
+    }
+}
+
+// 无人值守无法输入动态码：开启两步验证的账号请改用 enrollment_token
+async fn password_login(client: &ApiClient, host: &Host, email: &str, password: &str) -> Result<(), AppError> {
+    let payload = UserLoginDTO { email: email.to_string(), user_password: password.to_string() };
+    let token = client.login(&payload).await?.into_token()?;
+    Api::set_auth_token(host, &token).await
+}
+
+fn relogin(api_base: &str, gate: &version::VersionGate, host: &Host, email: &str, password: &str) -> Reauth {
+    let (api_base, gate, host, email, password) = (api_base.to_string(), gate.clone(), host.clone(), email.to_string(), password.to_string());
+    Arc::new(move || {
+        let (api_base, gate, host, email, password) = (api_base.clone(), gate.clone(), host.clone(), email.clone(), password.clone());
+        Box::pin(async move {
+            password_login(&ApiClient::with_base(api_base).with_version_gate(gate), &host, &email, &password).await?;
+            log::info!("session expired, logged in again as {}", mask_email(&email));
+            Ok(())
+        })
+    })
+}
+
+// systemctl stop 发 SIGTERM；前台运行时 Ctrl+C 发 SIGINT
+#[cfg(unix)]
+async fn wait_for_shutdown() -> Result<(), AppError> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut term = signal(SignalKind::terminate())?;
+    let mut int = signal(SignalKind::interrupt())?;
+    tokio::select! {
+        _ = term.recv() => log::info!("received SIGTERM"),
+        _ = int.recv() => log::info!("received SIGINT"),
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown() -> Result<(), AppError> {
+    tokio::signal::ctrl_c().await?;
+    log::info!("received Ctrl+C");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(email: Option<&str>, password: Option<&str>, token: Option<&str>) -> DaemonConfig {
+        DaemonConfig {
+            email: email.map(Into::into),
+            password: password.map(Into::into),
+            enrollment_token: token.map(Into::into),
+            ..DaemonConfig::default()
+        }
+    }
+
+    #[test]
+    fn login_method_selection() {
+        let account = cfg(Some("a@b.c"), Some("pw"), Some("tok"));
+        // 账号优先，即使也配了注册令牌、已有 token
+        assert_eq!(
+            login_method(&account, true, true).unwrap(),
+            LoginMethod::Password { email: "a@b.c", password: "pw" }
+        );
+        let enroll = cfg(None, None, Some("tok"));
+        assert_eq!(login_method(&enroll, false, true).unwrap(), LoginMethod::Enroll("tok"));
+        // 已入网后沿用设备凭证
+        assert_eq!(login_method(&enroll, true, true).unwrap(), LoginMethod::Stored);
+        // 只配了一半账号信息不算账号登录
+        assert_eq!(login_method(&cfg(Some("a@b.c"), None, None), false, true).unwrap(), LoginMethod::Stored);
+        assert!(matches!(login_method(&cfg(Some("a@b.c"), None, None), false, false), Err(AppError::InvalidInput(_))));
+        assert!(login_method(&enroll, true, false).is_err());
+    }
+}
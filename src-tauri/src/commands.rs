@@ -1,290 +1,142 @@
-use crate::api::ApiClient;
-use crate::error::AppError;
-use crate::models::{EmailCodeLoginDTO, SendCodeDTO, UserLoginDTO, UserLoginVO, UserRegisterDTO};
-use serde_json::json;
-use std::sync::Arc;
-use std::{
-    fs,
-    path::{Path, PathBuf},
+use hashtreasure_core::accounts::{self, Account};
+use hashtreasure_core::api::ApiClient;
+use hashtreasure_core::device_auth::{self, DeviceAuthorization};
+use hashtreasure_core::device_reg::{self, DeviceVo};
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::host::Host;
+use hashtreasure_core::i18n::{self, Locale};
+use hashtreasure_core::miner::{self, MiningManager};
+use hashtreasure_core::referral::{self, Cached};
+use hashtreasure_core::remote_config;
+use hashtreasure_core::updater::{self, PendingUpdate, UpdateChannel, UpdateInfo};
+use hashtreasure_core::version::{self, VersionInfo};
+use crate::settings::{self, CloseBehavior};
+use hashtreasure_core::api::Api;
+use hashtreasure_core::models::{
+    mask_email, ChangePasswordDTO, CodeType, EmailCodeLoginDTO, InviteInfoVO, InvitedUserVO, PageVO, PayoutAccountDTO,
+    PayoutAccountDeleteDTO, PayoutAccountVO, PayoutType, RecoveryCodesVO, ReferralEarningsVO, ResetPasswordDTO, SendCodeDTO,
+    TotpCodeDTO, TotpEnrollVO, TwoFactorStatusVO, TwoFactorVerifyDTO, UpdateProfileDTO, UserLoginDTO, UserLoginVO, UserProfileVO, UserRegisterDTO,
+    validate_device_name,
 };
-use tauri::{AppHandle, State};
-use tauri::Emitter; // 引入 Emitter trait，才能使用 app.emit()
-use tauri_plugin_store::StoreBuilder;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::{Child as TokioChild, Command as TokioCommand};
-use tokio::sync::Mutex;
-
-const STORE_PATH: &str = "store.dat";
-
-// ======= 固定矿池与钱包（后端写死，不给前端改） =======
-const C3POOL_USER: &str = "45MMv63J3y3751BLryGrDgdXfqX1BC2aNKE1ULUNygB5Dqtr8gibaV4R5kfXfMgSedSWA4RsswmYs63zYS8UC2xsJd289Qt";
-const C3POOL_WORKER: Option<&str> = Some("HashTreasure-CPU");
-const CPU_THREADS: Option<u16> = None; // None => 让 XMRig 自适应
-
-#[derive(Default)]
-pub struct MiningManager {
-    child: Mutex<Option<TokioChild>>,       // 当前挖矿子进程
-    last_hashrate: Arc<Mutex<Option<f64>>>, // 最近一次解析到的 10s hashrate
-    last_algo: Arc<Mutex<Option<String>>>,  // 最近一次解析到的算法（如 rx/0）
-}
-
-impl MiningManager {
-    async fn start(&self, app: AppHandle) -> Result<(), String> {
-        let mut child_guard = self.child.lock().await;
-        if child_guard.is_some() {
-            return Err("CPU mining is already running".into());
-        }
-        let bin = if cfg!(target_os = "windows") { "xmrig.exe" } else { "xmrig" };
-        let exe_path = std::env::current_exe().map_err(|e| format!("get current_exe failed: {e}"))?;
-        let xmrig_path = exe_path
-            .parent()
-            .ok_or_else(|| "resolve xmrig binary path failed (no parent)".to_string())?
-            .join(bin);
-        if C3POOL_USER.trim().is_empty() {
-            return Err("C3POOL_USER 为空，请在 commands.rs 中填写你的钱包/账号".into());
-        }
-        // 写配置 —— 优先与 xmrig.exe 同目录，其次回落到 %TEMP%
-        let exe_dir = xmrig_path.parent().ok_or_else(|| "xmrig path has no parent".to_string())?;
-        let cfg_path = match write_xmrig_config_into(exe_dir) {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("[miner] write config to exe_dir failed: {e}; falling back to temp dir");
-                write_xmrig_config_temp()?
-            }
-        };
-        let mut cmd = TokioCommand::new(&xmrig_path);
-        cmd.current_dir(exe_dir);
-        cmd.arg("--config").arg(&cfg_path); // 显式指定，双保险
-        cmd.arg("--tls");
-        if let Some(t) = CPU_THREADS { cmd.arg("-t").arg(t.to_string()); }
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
-        eprintln!("[miner] launching {} with --config {}", xmrig_path.display(), cfg_path.display());
-        let mut child = cmd.spawn().map_err(|e| format!("spawn xmrig failed: {e}"))?;
-        // stdout 读行：解析 hashrate 与算法，并向前端广播事件
-        if let Some(stdout) = child.stdout.take() {
-            let last_hashrate = Arc::clone(&self.last_hashrate);
-            let last_algo = Arc::clone(&self.last_algo);
-            let app_for_emit = app.clone();
-            tokio::spawn(async move {
-                let mut lines = BufReader::new(stdout).lines();
-                loop {
-                    match lines.next_line().await {
-                        Ok(Some(line)) => {
-                            println!("{line}");
-                            // 解析 10s hashrate
-                            if let Some(pos) = line.find("speed 10s/") {
-                                let nums: Vec<f64> = line[pos..]
-                                    .split_whitespace()
-                                    .filter_map(|s| {
-                                        let s = s.trim().trim_end_matches(',');
-                                        s.replace(',', ".").parse::<f64>().ok()
-                                    })
-                                    .collect();
-                                if let Some(h10) = nums.first().copied() {
-                                    let mut g = last_hashrate.lock().await;
-                                    *g = Some(h10);
-                                    let _ = app_for_emit.emit("cpu_hashrate", h10);
-                                }
-                            }
-                            // 解析算法（如 algo rx/0）
-                            if let Some(ai) = line.find("algo ") {
-                                let tail = &line[ai + 5..];
-                                let algo = tail
-                                    .split_whitespace()
-                                    .next()
-                                    .unwrap_or("")
-                                    .trim_matches(|c: char| c == ',' || c == ';')
-                                    .to_string();
-                                if !algo.is_empty() {
-                                    let mut a = last_algo.lock().await;
-                                    *a = Some(algo.clone());
-                                    let _ = app_for_emit.emit("cpu_algo", algo);
-                                }
-                            }
-                        }
-                        Ok(None) => break,
-                        Err(e) => {
-                            eprintln!("[xmrig stdout] read error: {e}");
-                            break;
-                        }
-                    }
-                }
-            });
-        }
-        // stderr 读行（仅打印）
-        if let Some(stderr) = child.stderr.take() {
-            tokio::spawn(async move {
-                let mut lines = BufReader::new(stderr).lines();
-                while let Ok(Some(line)) = lines.next_line().await {
-                    eprintln!("{line}");
-                }
-            });
-        }
-        *child_guard = Some(child);
-        Ok(())
-    }
-    // 需要在 main.rs 中调用，所以设为 pub
-    pub async fn stop(&self) -> Result<(), String> {
-        let mut guard = self.child.lock().await;
-        match guard.take() {
-            Some(mut child) => {
-                if let Err(e) = child.kill().await {
-                    eprintln!("xmrig kill error (maybe already exited): {e}");
-                }
-                let _ = child.wait().await;
-                Ok(())
-            }
-            None => Err("CPU mining is not running".into()),
-        }
-    }
-    async fn get_hashrate(&self) -> Option<f64> {
-        self.last_hashrate.lock().await.clone()
-    }
-    async fn get_algo(&self) -> Option<String> {
-        self.last_algo.lock().await.clone()
-    }
-    async fn is_running(&self) -> bool {
-        self.child.lock().await.is_some()
-    }
-}
-
-// 写配置到 xmrig.exe 同目录（优先方案）
-fn write_xmrig_config_into(dir: &Path) -> Result<PathBuf, String> {
-    let cfg_path = dir.join("config.json");
-    write_xmrig_config_core(&cfg_path)?;
-    Ok(cfg_path)
-}
-// 写配置到 %TEMP%\hash_treasure\xmrig.json（备用方案）
-fn write_xmrig_config_temp() -> Result<PathBuf, String> {
-    let dir = std::env::temp_dir().join("hash_treasure");
-    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
-    let cfg_path = dir.join("xmrig.json");
-    write_xmrig_config_core(&cfg_path)?;
-    Ok(cfg_path)
-}
-// 仅使用配置文件配置 HTTP API / 矿池 / DNS 行为
-fn write_xmrig_config_core(cfg_path: &Path) -> Result<(), String> {
-    // 主/备：端口 33333 + TLS，与 "--tls" 保持一致
-    let mut pool_main = json!({
-        "url": "auto.c3pool.org:33333",
-        "user": C3POOL_USER,
-        "keepalive": true,
-        "tls": true,
-        "sni": true
-    });
-    if let Some(w) = C3POOL_WORKER {
-        pool_main["rig-id"] = json!(w);
-    }
-    let mut pool_backup = json!({
-        "url": "auto.c3pool.org:33333",
-        "user": C3POOL_USER,
-        "keepalive": true,
-        "tls": true,
-        "sni": true
-    });
-    if let Some(w) = C3POOL_WORKER {
-        pool_backup["rig-id"] = json!(w);
-    }
-    let config = json!({
-        "autosave": true,
-        "print-time": 30,
-        "dns": { "ipv6": false },
-        "cpu": { "huge-pages": false },
-        "pools": [ pool_main, pool_backup ],
-        "http": {
-            "enabled": true,
-            "host": "127.0.0.1",
-            "port": 21550,
-            "restricted": true
-        }
-    });
-    fs::write(cfg_path, serde_json::to_vec_pretty(&config).unwrap()).map_err(|e| e.to_string())?;
-    Ok(())
-}
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
 
 // ======= Tauri commands =======
 
 #[tauri::command]
-pub async fn start_cpu_mining(app: AppHandle, manager: State<'_, MiningManager>) -> Result<(), String> {
-    manager.start(app).await
+pub async fn start_cpu_mining(host: State<'_, Host>, manager: State<'_, Arc<MiningManager>>) -> Result<(), AppError> {
+    manager.start(host.inner().clone()).await
 }
 #[tauri::command]
-pub async fn stop_cpu_mining(manager: State<'_, MiningManager>) -> Result<(), String> {
+pub async fn stop_cpu_mining(manager: State<'_, Arc<MiningManager>>) -> Result<(), AppError> {
     manager.stop().await
 }
 #[tauri::command]
-pub async fn get_cpu_hashrate(manager: State<'_, MiningManager>) -> Result<Option<f64>, String> {
+pub async fn pause_cpu_mining(manager: State<'_, Arc<MiningManager>>) -> Result<(), AppError> {
+    manager.pause().await
+}
+#[tauri::command]
+pub async fn resume_cpu_mining(manager: State<'_, Arc<MiningManager>>) -> Result<(), AppError> {
+    manager.resume().await
+}
+#[tauri::command]
+pub async fn get_cpu_hashrate(manager: State<'_, Arc<MiningManager>>) -> Result<Option<f64>, AppError> {
     Ok(manager.get_hashrate().await)
 }
 #[tauri::command]
-pub async fn is_cpu_mining(manager: State<'_, MiningManager>) -> Result<bool, String> {
+pub async fn is_cpu_mining(manager: State<'_, Arc<MiningManager>>) -> Result<bool, AppError> {
     Ok(manager.is_running().await)
 }
 #[tauri::command]
-pub async fn get_cpu_algo(manager: State<'_, MiningManager>) -> Result<Option<String>, String> {
+pub async fn get_cpu_algo(manager: State<'_, Arc<MiningManager>>) -> Result<Option<String>, AppError> {
     Ok(manager.get_algo().await)
 }
 
 // ======= 登录/注册 + 本地 Token（与你现有逻辑一致） =======
-fn get_token_from_store(app: &AppHandle) -> Result<String, AppError> {
-    let path = PathBuf::from(STORE_PATH);
-    let store = StoreBuilder::new(app, path).build()?;
-    // 重新加载数据以确保读取最新值
-    let _ = store.reload();
-    match store.get("auth_token") {
-        Some(v) if v.is_string() => Ok(v.as_str().unwrap().to_string()),
-        _ => Err(AppError::ApiError("Not logged in".to_string())),
-    }
+// token 存在当前账号的命名空间下（见 accounts）
+fn get_token_from_store(host: &Host) -> Result<String, AppError> {
+    host.get_str(&accounts::scoped(host, "auth_token"))
+        .ok_or_else(|| AppError::ApiError("Not logged in".to_string()))
 }
-fn save_token(app: &AppHandle, token: &str) -> Result<(), AppError> {
-    let path = PathBuf::from(STORE_PATH);
-    let store = StoreBuilder::new(app, path).build()?;
-    store.set("auth_token", serde_json::Value::String(token.to_string()));
-    store.save()?;
-    Ok(())
+fn save_token(host: &Host, token: &str) -> Result<(), AppError> {
+    host.store().set(&accounts::scoped(host, "auth_token"), serde_json::Value::String(token.to_string()))
 }
-fn remove_token(app: &AppHandle) -> Result<(), AppError> {
-    let path = PathBuf::from(STORE_PATH);
-    let store = StoreBuilder::new(app, path).build()?;
-    let _ = store.delete("auth_token");
-    store.save()?;
-    Ok(())
+fn remove_token(host: &Host) -> Result<(), AppError> {
+    host.store().delete(&accounts::scoped(host, "auth_token"))
+}
+
+/// 登录成功：先激活对应账号，再把 token 写入它的命名空间。
+/// 响应里没有 uid 时按扫码登录的方式处理（先写入临时位置，再查资料确定账号）。
+async fn save_login(
+    host: &Host,
+    api_client: &ApiClient,
+    response: UserLoginVO,
+    email: Option<&str>,
+) -> Result<(), AppError> {
+    let uid = response.uid;
+    let name = response.user_name.clone();
+    let token = response.into_token()?;
+    match uid.map(|u| u.to_string()).or_else(|| email.map(|e| e.to_string())) {
+        Some(id) => {
+            accounts::activate(host, &id, name.as_deref().or(email), email)?;
+            save_token(host, &token)
+        }
+        None => {
+            let pending = accounts::begin_pending(host)?;
+            save_token(&pending, &token)?;
+            finish_pending_login(host, &pending, api_client).await
+        }
+    }
+}
+
+/// 待定位置里的 token 已可用：用它查询资料得到账号 id，迁入该账号并激活；
+/// 失败时丢弃待定 token，当前账号始终不受影响
+async fn finish_pending_login(host: &Host, pending: &Host, api_client: &ApiClient) -> Result<(), AppError> {
+    let result = match api_client.get_profile(pending).await {
+        Ok(p) => match p.uid.map(|u| u.to_string()).or_else(|| p.email.clone()) {
+            Some(id) => {
+                let name = p.user_name.as_deref().or(p.email.as_deref());
+                accounts::finish_pending(host, &id, name, p.email.as_deref()).map(|_| ())
+            }
+            None => Err(AppError::ApiError("Profile has no uid".to_string())),
+        },
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        accounts::discard_pending(host)?;
+    }
+    result
 }
 
 #[tauri::command]
 pub async fn login(
     email: String,
     password: String,
-    app: AppHandle,
+    host: State<'_, Host>,
     api_client: State<'_, ApiClient>,
 ) -> Result<String, AppError> {
-    log::info!("Attempting to login for user: {}", email);
-    let payload = UserLoginDTO { email, user_password: password };
+    log::info!("Attempting to login for user: {}", mask_email(&email));
+    let payload = UserLoginDTO { email: email.clone(), user_password: password };
     let response: UserLoginVO = api_client.login(&payload).await?;
-    if let Some(token) = response.token {
-        save_token(&app, &token)?;
-        Ok("Login successful".to_string())
-    } else {
-        Err(AppError::ApiError("Login success but no token received".to_string()))
-    }
+    // 开启两步验证时返回 TWO_FACTOR_REQUIRED，前端带 challengeId 调用 submit_2fa
+    save_login(&host, &api_client, response, Some(&email)).await?;
+    Ok("Login successful".to_string())
 }
 #[tauri::command]
 pub async fn login_by_code(
     email: String,
     code: String,
-    app: AppHandle,
+    host: State<'_, Host>,
     api_client: State<'_, ApiClient>,
 ) -> Result<String, AppError> {
-    log::info!("Attempting to login with code for email: {}", email);
-    let payload = EmailCodeLoginDTO { email, code };
+    log::info!("Attempting to login with code for email: {}", mask_email(&email));
+    let payload = EmailCodeLoginDTO { email: email.clone(), code };
     let response: UserLoginVO = api_client.login_by_code(&payload).await?;
-    if let Some(token) = response.token {
-        save_token(&app, &token)?;
-        Ok("Login with code successful".to_string())
-    } else {
-        Err(AppError::ApiError("Login success but no token received".to_string()))
-    }
+    // 开启两步验证时返回 TWO_FACTOR_REQUIRED，前端带 challengeId 调用 submit_2fa
+    save_login(&host, &api_client, response, Some(&email)).await?;
+    Ok("Login with code successful".to_string())
 }
 #[tauri::command]
 pub async fn register(
@@ -296,14 +148,14 @@ pub async fn register(
     alipay_name: Option<String>,
     invite_code: Option<String>,
     phone: Option<String>,
-    app: AppHandle,
+    host: State<'_, Host>,
     api_client: State<'_, ApiClient>,
 ) -> Result<String, AppError> {
     log::info!("Attempting to register new user: {}", username);
     let payload = UserRegisterDTO {
         user_name: username,
         user_password: password,
-        email,
+        email: email.clone(),
         code,
         reg_into: "client".to_string(),
         alipay_phone,
@@ -312,29 +164,457 @@ pub async fn register(
         phone,
     };
     let response: UserLoginVO = api_client.register(&payload).await?;
-    if let Some(token) = response.token {
-        save_token(&app, &token)?;
-        Ok("Registration successful".to_string())
-    } else {
-        Err(AppError::ApiError("Register success but no token received".to_string()))
-    }
+    save_login(&host, &api_client, response, Some(&email)).await?;
+    Ok("Registration successful".to_string())
 }
 #[tauri::command]
 pub async fn send_code(
     email: String,
-    r#type: String,
+    r#type: CodeType,
     api_client: State<'_, ApiClient>,
 ) -> Result<(), AppError> {
-    log::info!("Sending code to email: {}", email);
+    log::info!("Sending code to email: {}", mask_email(&email));
     let payload = SendCodeDTO { email, r#type };
     api_client.send_code(&payload).await
 }
 #[tauri::command]
-pub async fn get_auth_token(app: AppHandle) -> Result<Option<String>, AppError> {
-    Ok(get_token_from_store(&app).ok())
+pub async fn reset_password(
+    email: String,
+    code: String,
+    new_password: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
+    log::info!("Resetting password for email: {}", mask_email(&email));
+    let payload = ResetPasswordDTO { email, code, new_password };
+    payload.validate()?;
+    api_client.reset_password(&payload).await?;
+    // 该邮箱对应账号的旧凭证在服务端已失效，本地一并清除，要求重新登录；
+    // 重置的不是已保存的账号时不动任何本地数据
+    if let Some(account) = accounts::find_by_email(&host, &payload.email) {
+        log::info!("clearing credentials of account {}", account.id);
+        accounts::clear_credentials(&host, &account.id)?;
+    }
+    Ok(())
+}
+#[tauri::command]
+pub async fn get_auth_token(host: State<'_, Host>) -> Result<Option<String>, AppError> {
+    Ok(get_token_from_store(&host).ok())
 }
 #[tauri::command]
-pub async fn logout(app: AppHandle) -> Result<(), AppError> {
+pub async fn logout(
+    host: State<'_, Host>,
+    manager: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
     log::info!("User logging out");
-    remove_token(&app)
+    // 扫码登录写入的是 access/refresh token，一并清除
+    Api::clear_tokens(&host).await;
+    remove_token(&host)?;
+    // 多账号：退出即移除当前账号，与 remove_account 相同——先停矿，切到剩下的账号（若有）后以新 worker 重启，
+    // 不会继续以已退出账号的 rig-id 挖矿
+    match accounts::active(&host) {
+        Some(account) => {
+            remove_active_account(&host, &manager, &api_client, &account.id).await?;
+        }
+        None => {
+            referral::clear_cache(&host)?;
+            if manager.is_running().await {
+                manager.stop().await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// ======= 版本 =======
+/// 本机客户端、XMRig 与接口协议版本，以及服务端下发的最低/推荐版本
+#[tauri::command]
+pub async fn get_version_info(host: State<'_, Host>) -> Result<VersionInfo, AppError> {
+    Ok(version::info(&host, miner::xmrig_version().await))
+}
+
+// ======= 应用内升级 =======
+#[tauri::command]
+pub async fn get_update_channel(host: State<'_, Host>) -> Result<UpdateChannel, AppError> {
+    Ok(updater::channel(&host))
+}
+#[tauri::command]
+pub async fn set_update_channel(channel: UpdateChannel, host: State<'_, Host>) -> Result<(), AppError> {
+    log::info!("update channel set to {:?}", channel);
+    updater::set_channel(&host, channel)
+}
+/// 查询本机更新通道上的新版本，没有时返回 null
+#[tauri::command]
+pub async fn check_for_update(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<Option<UpdateInfo>, AppError> {
+    updater::check(api_client.api(), &host).await
+}
+/// 下载并校验安装包（推送 update:progress / update:ready），完成后等待用户确认重启
+#[tauri::command]
+pub async fn download_update(
+    info: UpdateInfo,
+    app: AppHandle,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<PendingUpdate, AppError> {
+    let dir = app.path().app_cache_dir().map_err(|_| AppError::PathError)?.join("updates");
+    updater::download(api_client.api(), &host, &info, &dir).await
+}
+/// 重启安装：先停止挖矿，再启动安装程序并退出
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    host: State<'_, Host>,
+    manager: State<'_, Arc<MiningManager>>,
+) -> Result<(), AppError> {
+    let pending = updater::pending(&host).ok_or_else(|| AppError::UpdateFailed("no update downloaded".into()))?;
+    // 文件被替换或损坏时不停挖；launch_installer 启动前还会再校验一次
+    updater::verify_pending(&pending)?;
+    if manager.is_running().await {
+        manager.stop().await?;
+    }
+    updater::launch_installer(&pending)?;
+    // Windows 安装程序会替换正在运行的文件并自行拉起新版本，这里只需退出
+    if cfg!(target_os = "windows") {
+        app.exit(0);
+        Ok(())
+    } else {
+        app.restart()
+    }
+}
+
+// ======= 远程配置 =======
+/// 服务端下发的功能开关（未下发时为空表，前端按关闭处理）
+#[tauri::command]
+pub async fn get_feature_flags(host: State<'_, Host>) -> Result<BTreeMap<String, bool>, AppError> {
+    Ok(remote_config::current(&host).map(|c| c.features).unwrap_or_default())
+}
+
+// ======= 多账号 =======
+/// 已保存的账号列表。旧版本升级上来的单账号数据在这里补登记为第一个账号。
+#[tauri::command]
+pub async fn list_accounts(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<Vec<Account>, AppError> {
+    if accounts::list(&host).is_empty() && Api::access_token(&host).await.is_some() {
+        match api_client.get_profile(&host).await {
+            Ok(p) => match p.uid.map(|u| u.to_string()).or_else(|| p.email.clone()) {
+                Some(id) => {
+                    accounts::activate(&host, &id, p.user_name.as_deref().or(p.email.as_deref()), p.email.as_deref())?;
+                }
+                None => log::warn!("migrating legacy login failed: profile has no uid"),
+            },
+            Err(e) => log::warn!("migrating legacy login failed: {}", e),
+        }
+    }
+    Ok(accounts::list(&host))
+}
+/// 切换当前账号：正在挖矿时先停下，把本机绑定到新账号后以新的 worker 重启
+#[tauri::command]
+pub async fn switch_account(
+    account_id: String,
+    host: State<'_, Host>,
+    manager: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+) -> Result<Account, AppError> {
+    let was_running = manager.is_running().await;
+    if was_running {
+        manager.stop().await?;
+    }
+    let account = accounts::switch(&host, &account_id)?;
+    referral::clear_cache(&host)?;
+    rebind_device(&host, &api_client, &account).await;
+    if was_running {
+        manager.start(host.inner().clone()).await?;
+    }
+    host.emit("account:switched", Some(&account));
+    Ok(account)
+}
+/// 删除已保存的账号；删除的是当前账号时与 switch_account 一样切到下一个
+#[tauri::command]
+pub async fn remove_account(
+    account_id: String,
+    host: State<'_, Host>,
+    manager: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+) -> Result<Option<Account>, AppError> {
+    let was_active = accounts::active(&host).map(|a| a.id == account_id).unwrap_or(false);
+    if !was_active {
+        return accounts::remove(&host, &account_id);
+    }
+    remove_active_account(&host, &manager, &api_client, &account_id).await
+}
+
+// 移除当前账号：停矿，切到下一个账号并重新绑定本机，原先在挖矿则以新账号的 worker 重启
+async fn remove_active_account(
+    host: &Host,
+    manager: &MiningManager,
+    api_client: &ApiClient,
+    account_id: &str,
+) -> Result<Option<Account>, AppError> {
+    let was_running = manager.is_running().await;
+    if was_running {
+        manager.stop().await?;
+    }
+    referral::clear_cache(host)?;
+    let next = accounts::remove(host, account_id)?;
+    if let Some(account) = &next {
+        rebind_device(host, api_client, account).await;
+        if was_running {
+            manager.start(host.clone()).await?;
+        }
+    }
+    host.emit("account:switched", &next);
+    Ok(next)
+}
+
+// 新账号名下还没有本机时注册；离线等失败不阻断切换，心跳会在恢复后自动补注册
+async fn rebind_device(host: &Host, api_client: &ApiClient, account: &Account) {
+    let username = account.name.as_deref().unwrap_or(&account.id);
+    match device_reg::ensure_registered(api_client.api(), host, username).await {
+        Ok(id) => log::info!("device bound to account {} as {}", account.id, id),
+        Err(e) => log::warn!("binding device to account {} failed: {}", account.id, e),
+    }
+}
+
+// ======= 两步验证 =======
+/// 登录第二步：提交身份验证器动态码或恢复码，成功后保存 token
+#[tauri::command]
+pub async fn submit_2fa(
+    challenge_id: String,
+    code: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<String, AppError> {
+    let payload = TwoFactorVerifyDTO { challenge_id, code: code.trim().to_string() };
+    payload.validate()?;
+    let response = api_client.verify_2fa(&payload).await?;
+    save_login(&host, &api_client, response, None).await?;
+    Ok("Login successful".to_string())
+}
+#[tauri::command]
+pub async fn get_2fa_status(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<TwoFactorStatusVO, AppError> {
+    api_client.get_2fa_status(&host).await
+}
+/// 开启 TOTP 第一步：返回 otpauth URI（前端渲染二维码）与手动输入用的密钥
+#[tauri::command]
+pub async fn enroll_totp(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<TotpEnrollVO, AppError> {
+    api_client.enroll_totp(&host).await
+}
+/// 开启 TOTP 第二步：用动态码确认，返回一次性恢复码
+#[tauri::command]
+pub async fn confirm_totp(
+    code: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<RecoveryCodesVO, AppError> {
+    let payload = TotpCodeDTO { code: code.trim().to_string() };
+    payload.validate_totp()?;
+    api_client.confirm_totp(&host, &payload).await
+}
+/// 关闭两步验证，动态码或恢复码均可
+#[tauri::command]
+pub async fn disable_totp(code: String, host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<(), AppError> {
+    let payload = TotpCodeDTO { code: code.trim().to_string() };
+    payload.validate_totp_or_recovery()?;
+    api_client.disable_totp(&host, &payload).await
+}
+/// 重新生成恢复码，旧的立即作废
+#[tauri::command]
+pub async fn regenerate_recovery_codes(
+    code: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<RecoveryCodesVO, AppError> {
+    let payload = TotpCodeDTO { code: code.trim().to_string() };
+    payload.validate_totp()?;
+    api_client.regenerate_recovery_codes(&host, &payload).await
+}
+
+// ======= 扫码登录（OAuth 设备授权） =======
+/// 申请 user code 与二维码内容，前端展示后调用 wait_device_login
+#[tauri::command]
+pub async fn start_device_login(api_client: State<'_, ApiClient>) -> Result<DeviceAuthorization, AppError> {
+    log::info!("Starting device authorization");
+    device_auth::start(api_client.api()).await
+}
+/// 轮询直到用户在手机上确认；批准后 token 写入 store 并激活对应账号
+#[tauri::command]
+pub async fn wait_device_login(
+    authorization: DeviceAuthorization,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
+    // 批准前不知道是哪个账号，token 先写入待定位置；轮询期间当前账号照常心跳、挖矿
+    let pending = accounts::begin_pending(&host)?;
+    match device_auth::poll_until_approved(api_client.api(), &pending, &authorization).await {
+        Ok(()) => finish_pending_login(&host, &pending, &api_client).await,
+        Err(e) => {
+            accounts::discard_pending(&host)?;
+            Err(e)
+        }
+    }
+}
+
+// ======= 个人资料 / 收款账户 =======
+#[tauri::command]
+pub async fn get_profile(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<UserProfileVO, AppError> {
+    api_client.get_profile(&host).await
+}
+#[tauri::command]
+pub async fn update_profile(
+    user_name: Option<String>,
+    phone: Option<String>,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<UserProfileVO, AppError> {
+    let payload = UpdateProfileDTO { user_name, phone };
+    payload.validate()?;
+    log::info!("Updating profile: {:?}", payload);
+    api_client.update_profile(&host, &payload).await
+}
+#[tauri::command]
+pub async fn change_password(
+    old_password: String,
+    new_password: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
+    let payload = ChangePasswordDTO { old_password, new_password };
+    payload.validate()?;
+    log::info!("Changing password");
+    api_client.change_password(&host, &payload).await
+}
+#[tauri::command]
+pub async fn list_payout_accounts(
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<Vec<PayoutAccountVO>, AppError> {
+    api_client.list_payout_accounts(&host).await
+}
+#[tauri::command]
+pub async fn save_payout_account(
+    account_id: Option<i64>,
+    account_type: PayoutType,
+    account: String,
+    account_name: String,
+    code: String,
+    is_default: Option<bool>,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<PayoutAccountVO, AppError> {
+    let payload = PayoutAccountDTO {
+        account_id,
+        account_type,
+        account,
+        account_name,
+        code,
+        is_default: is_default.unwrap_or(false),
+    };
+    payload.validate()?;
+    log::info!("Saving payout account: {:?}", payload);
+    api_client.save_payout_account(&host, &payload).await
+}
+#[tauri::command]
+pub async fn delete_payout_account(
+    account_id: i64,
+    code: String,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
+    let payload = PayoutAccountDeleteDTO { code };
+    payload.validate()?;
+    log::info!("Deleting payout account #{}", account_id);
+    api_client.delete_payout_account(&host, account_id, &payload).await
+}
+
+// ======= 邀请 / 推广（带离线缓存） =======
+#[tauri::command]
+pub async fn get_invite_info(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<Cached<InviteInfoVO>, AppError> {
+    referral::invite_info(&api_client, &host).await
+}
+#[tauri::command]
+pub async fn get_invited_users(
+    page: i64,
+    size: i64,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<Cached<PageVO<InvitedUserVO>>, AppError> {
+    referral::invited_users(&api_client, &host, page, size).await
+}
+#[tauri::command]
+pub async fn get_referral_earnings(
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<Cached<ReferralEarningsVO>, AppError> {
+    referral::earnings(&api_client, &host).await
+}
+
+// ======= 设置 =======
+#[tauri::command]
+pub async fn get_close_behavior(host: State<'_, Host>) -> Result<CloseBehavior, AppError> {
+    Ok(settings::close_behavior(&host))
+}
+#[tauri::command]
+pub async fn set_close_behavior(behavior: CloseBehavior, host: State<'_, Host>) -> Result<(), AppError> {
+    settings::set_close_behavior(&host, behavior)
+}
+
+// ======= 诊断 =======
+/// 导出诊断包，返回 zip 文件路径；dest_dir 为空时放到下载目录
+#[tauri::command]
+pub async fn export_diagnostics(app: AppHandle, dest_dir: Option<String>) -> Result<String, AppError> {
+    let path = crate::diagnostics::export(&app, dest_dir.map(PathBuf::from)).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// ======= 语言：后端产生的错误/通知文案跟随前端语言 =======
+#[tauri::command]
+pub async fn set_locale(locale: Locale, app: AppHandle, host: State<'_, Host>) -> Result<(), AppError> {
+    i18n::set_locale(&host, locale)?;
+    crate::tray::refresh(&app).await;
+    Ok(())
+}
+#[tauri::command]
+pub async fn get_locale() -> Result<Locale, AppError> {
+    Ok(i18n::locale())
+}
+
+// ======= 设备管理 =======
+#[tauri::command]
+pub async fn list_devices(host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<Vec<DeviceVo>, AppError> {
+    device_reg::list_devices(api_client.api(), &host)
+        .await
+        .map_err(AppError::from)
+}
+#[tauri::command]
+pub async fn rename_device(name: String, host: State<'_, Host>, api_client: State<'_, ApiClient>) -> Result<(), AppError> {
+    let name = validate_device_name(&name)?;
+    device_reg::rename_current_device(api_client.api(), &host, name)
+        .await
+        .map_err(AppError::from)
+}
+/// 解绑的是本机时停止挖矿：本机的 deviceId 与挖矿凭证已随之清除
+#[tauri::command]
+pub async fn unbind_device(
+    device_id: String,
+    host: State<'_, Host>,
+    manager: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+) -> Result<(), AppError> {
+    let was_current = device_reg::unbind_device(api_client.api(), &host, &device_id).await?;
+    if was_current && manager.is_running().await {
+        log::info!("current device unbound, stopping miner");
+        manager.stop().await?;
+    }
+    Ok(())
+}
+#[tauri::command]
+pub async fn reregister_device(
+    name: Option<String>,
+    host: State<'_, Host>,
+    api_client: State<'_, ApiClient>,
+) -> Result<String, AppError> {
+    let name = name.as_deref().map(validate_device_name).transpose()?;
+    device_reg::reregister_device(api_client.api(), &host, name)
+        .await
+        .map_err(AppError::from)
 }
@@ -1,26 +1,60 @@
-use tauri::{AppHandle};
-use crate::api::Api;
-use crate::device_reg::ensure_registered;
-use crate::heartbeat::{spawn_heartbeat, HeartbeatPayload};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::State;
+use hashtreasure_core::api::ApiClient;
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::device_reg::ensure_registered;
+use hashtreasure_core::heartbeat::{sampler, spawn_heartbeat};
+use hashtreasure_core::host::Host;
+use hashtreasure_core::miner::MiningManager;
+use hashtreasure_core::remote::spawn_remote_channel;
+
+// 心跳与远程通道每个进程只跑一份：循环每轮都从 store 读取当前 deviceId，重复调用无需再起新循环
+static HEARTBEAT_STARTED: AtomicBool = AtomicBool::new(false);
+static REMOTE_STARTED: AtomicBool = AtomicBool::new(false);
+// 以下命令由前端传入后端地址：沿用 ApiClient 的版本门槛处理，只换地址
 
 #[tauri::command]
-pub async fn cmd_login(app: AppHandle, base: String, user: String, pass: String) -> Result<(), String> {
-    let api = Api::new(base);
-    api.login(&app, &user, &pass).await.map_err(|e| e.to_string())
+pub async fn cmd_login(host: State<'_, Host>, api_client: State<'_, ApiClient>, base: String, user: String, pass: String) -> Result<(), AppError> {
+    let api = api_client.api().rebase(base);
+    Ok(api.login(&host, &user, &pass).await?)
 }
 
 #[tauri::command]
-pub async fn cmd_bootstrap(app: AppHandle, base: String, username: String) -> Result<String, String> {
-    let api = Api::new(base.clone());
-    ensure_registered(&api, &app, &username).await.map_err(|e| e.to_string())
+pub async fn cmd_bootstrap(host: State<'_, Host>, api_client: State<'_, ApiClient>, base: String, username: String) -> Result<String, AppError> {
+    let api = api_client.api().rebase(base);
+    Ok(ensure_registered(&api, &host, &username).await?)
+}
+
+#[tauri::command]
+pub async fn cmd_start_heartbeat(
+    host: State<'_, Host>,
+    miner: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+    base: String,
+    device_id: String,
+) -> Result<(), AppError> {
+    if HEARTBEAT_STARTED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let api = api_client.api().rebase(base);
+    let miner = miner.inner().clone();
+    spawn_heartbeat(api, host.inner().clone(), miner.clone(), device_id, sampler(miner)).await;
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn cmd_start_heartbeat(app: AppHandle, base: String, device_id: String) -> Result<(), String> {
-    let api = Api::new(base.clone());
-    spawn_heartbeat(api, app.clone(), device_id, || {
-        // TODO: 替换为真实采样数据
-        HeartbeatPayload { cpuUsage: "20".into(), gpuUsage: "35".into(), memoryUsage: 40.5, cpuHashrate: 1200.3, gpuHashrate: 45000.7 }
-    }).await;
+pub async fn cmd_start_remote(
+    host: State<'_, Host>,
+    miner: State<'_, Arc<MiningManager>>,
+    api_client: State<'_, ApiClient>,
+    base: String,
+    device_id: String,
+) -> Result<(), AppError> {
+    if REMOTE_STARTED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let api = api_client.api().rebase(base);
+    spawn_remote_channel(api, host.inner().clone(), miner.inner().clone(), device_id).await;
     Ok(())
 }
@@ -0,0 +1,125 @@
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{System, SystemExt};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+use hashtreasure_core::device_id::{ensure_local_fingerprint, get_cloud_device_id};
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::host::Host;
+use hashtreasure_core::miner::{xmrig_config_paths, MiningManager};
+use hashtreasure_core::redact::redact;
+
+// 日志文件名（位于 app_log_dir，由 tauri-plugin-log 按大小轮转）
+pub const APP_LOG_NAME: &str = "hashtreasure";
+pub const XMRIG_LOG_NAME: &str = "xmrig";
+pub const LOG_MAX_FILE_SIZE: u128 = 5 * 1024 * 1024;
+pub const LOG_KEEP_FILES: usize = 5;
+
+const STORE_PATH: &str = "store.dat";
+// 服务端签发的挖矿凭证（含矿池密码），整项不进诊断包；多账号时键名带 "account:<id>:" 前缀
+const MINING_CREDENTIALS_KEY: &str = "mining_credentials";
+
+/// 打包日志、脱敏后的配置、挖矿状态、设备信息与版本号，返回 zip 路径
+pub async fn export(app: &AppHandle, dest_dir: Option<PathBuf>) -> Result<PathBuf, AppError> {
+    let log_dir = app.path().app_log_dir().map_err(|_| AppError::PathError)?;
+    let dest_dir = match dest_dir {
+        Some(d) => d,
+        None => app.path().download_dir().unwrap_or_else(|_| log_dir.clone()),
+    };
+    fs::create_dir_all(&dest_dir)?;
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let zip_path = dest_dir.join(format!("hashtreasure-diagnostics-{}.zip", ts));
+
+    // 需要 await 的部分先收集好，写 zip 放到阻塞线程
+    let host = app.state::<Host>();
+    let miner = app.state::<Arc<MiningManager>>().stats().await;
+    let fingerprint = ensure_local_fingerprint(&host).await.ok();
+    let device_id = get_cloud_device_id(&host).await;
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    let device = json!({
+        "fingerprint": fingerprint,
+        "deviceId": device_id,
+        "os": sys.name(),
+        "osVersion": sys.os_version(),
+        "kernelVersion": sys.kernel_version(),
+        "cpuCount": sys.cpus().len(),
+        "totalMemory": sys.total_memory(),
+    });
+    let version = json!({
+        "app": app.package_info().version.to_string(),
+        "name": app.package_info().name,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+    });
+    let store_path = app
+        .path()
+        .app_data_dir()
+        .map(|d| d.join(STORE_PATH))
+        .ok();
+
+    let out = zip_path.clone();
+    tokio::task::spawn_blocking(move || -> Result<(), AppError> {
+        let mut zip = ZipWriter::new(File::create(&out)?);
+        let opts = SimpleFileOptions::default();
+        add_dir(&mut zip, &log_dir, "logs", opts)?;
+        for cfg in xmrig_config_paths() {
+            if let Ok(text) = fs::read_to_string(&cfg) {
+                let name = cfg.file_name().and_then(|n| n.to_str()).unwrap_or("config.json");
+                add_text(&mut zip, &format!("config/{}", name), &text, opts)?;
+            }
+        }
+        if let Some(text) = store_path.and_then(|p| fs::read_to_string(p).ok()) {
+            add_text(&mut zip, "config/store.json", &strip_credentials(&text), opts)?;
+        }
+        add_text(&mut zip, "miner.json", &serde_json::to_string_pretty(&miner)?, opts)?;
+        add_text(&mut zip, "device.json", &serde_json::to_string_pretty(&device)?, opts)?;
+        add_text(&mut zip, "version.json", &serde_json::to_string_pretty(&version)?, opts)?;
+        zip.finish().map_err(|e| AppError::IoError(e.into()))?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))??;
+    log::info!("diagnostics exported to {}", zip_path.display());
+    Ok(zip_path)
+}
+
+fn strip_credentials(store: &str) -> String {
+    match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(store) {
+        Ok(mut map) => {
+            map.retain(|key, _| key.rsplit(':').next() != Some(MINING_CREDENTIALS_KEY));
+            serde_json::to_string_pretty(&map).unwrap_or_default()
+        }
+        // 解析不了时原样交给 redact
+        Err(_) => store.to_string(),
+    }
+}
+
+// 所有文本写入前都过一遍 redact，和日志管道保持同样的脱敏规则
+fn add_text(zip: &mut ZipWriter<File>, name: &str, text: &str, opts: SimpleFileOptions) -> Result<(), AppError> {
+    zip.start_file(name, opts).map_err(|e| AppError::IoError(e.into()))?;
+    zip.write_all(redact(text).as_bytes())?;
+    Ok(())
+}
+
+fn add_dir(zip: &mut ZipWriter<File>, dir: &Path, prefix: &str, opts: SimpleFileOptions) -> Result<(), AppError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Ok(()),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let text = String::from_utf8_lossy(&fs::read(&path)?).to_string();
+        add_text(zip, &format!("{}/{}", prefix, name), &text, opts)?;
+    }
+    Ok(())
+}
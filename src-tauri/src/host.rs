@@ -0,0 +1,64 @@
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::host::{EventSink, Host, KvStore};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreBuilder;
+
+// hashtreasure-core 的运行环境在桌面端的实现：store.dat + 前端事件
+
+const STORE_PATH: &str = "store.dat";
+
+fn store_err(e: tauri_plugin_store::Error) -> AppError {
+    AppError::StoreError(e.to_string())
+}
+
+/// tauri-plugin-store 的 store.dat
+pub struct TauriStore(AppHandle);
+
+impl KvStore for TauriStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        let store = StoreBuilder::new(&self.0, PathBuf::from(STORE_PATH)).build().ok()?;
+        // 重新加载数据以确保读取最新值
+        let _ = store.reload();
+        store.get(key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<(), AppError> {
+        let store = StoreBuilder::new(&self.0, PathBuf::from(STORE_PATH)).build().map_err(store_err)?;
+        let _ = store.reload();
+        store.set(key, value);
+        store.save().map_err(store_err)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let store = StoreBuilder::new(&self.0, PathBuf::from(STORE_PATH)).build().map_err(store_err)?;
+        let _ = store.reload();
+        let _ = store.delete(key);
+        store.save().map_err(store_err)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        StoreBuilder::new(&self.0, PathBuf::from(STORE_PATH))
+            .build()
+            .map(|store| {
+                let _ = store.reload();
+                store.keys()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 广播给前端（以及托盘的 listen_any）
+pub struct TauriEvents(AppHandle);
+
+impl EventSink for TauriEvents {
+    fn emit(&self, event: &str, payload: Value) {
+        let _ = self.0.emit(event, payload);
+    }
+}
+
+pub fn tauri_host(app: &AppHandle) -> Host {
+    Host::new(Arc::new(TauriStore(app.clone())), Arc::new(TauriEvents(app.clone())))
+}
@@ -1,43 +1,109 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod api;
+// 业务逻辑在 hashtreasure-core；这里只有 Tauri 命令、托盘、窗口设置与诊断包
 mod commands;
-mod error;
-mod models;
-mod device;
+mod commands_patch;
+mod diagnostics;
+mod host;
+mod settings;
+mod tray;
 
-use crate::commands::MiningManager;
+use crate::settings::CloseBehavior;
+use hashtreasure_core::host::Host;
+use hashtreasure_core::miner::MiningManager;
+use hashtreasure_core::{api, i18n, redact, remote_config, updater, version};
+use std::sync::Arc;
 use tauri::{Manager, WindowEvent};
-use tauri_plugin_log::{Builder as LogBuilder, Target as LogTarget, TargetKind};
+use tauri_plugin_log::{Builder as LogBuilder, RotationStrategy, Target as LogTarget, TargetKind};
 
 fn main() {
-    // 你的 ApiClient 构造
-    let api_client = api::ApiClient::new();
-
+    // XMRig 的输出量很大，单独写入 xmrig.log，不进入主日志与 webview
     let targets = [
         LogTarget::new(TargetKind::Stdout),
-        LogTarget::new(TargetKind::Webview),
+        LogTarget::new(TargetKind::Webview).filter(|m| m.target() != "xmrig"),
+        LogTarget::new(TargetKind::LogDir {
+            file_name: Some(diagnostics::APP_LOG_NAME.into()),
+        })
+        .filter(|m| m.target() != "xmrig"),
+        LogTarget::new(TargetKind::LogDir {
+            file_name: Some(diagnostics::XMRIG_LOG_NAME.into()),
+        })
+        .filter(|m| m.target() == "xmrig"),
     ];
 
     tauri::Builder::default()
-        // 1) 管理 ApiClient
-        .manage(api_client)
-        // 2) 管理挖矿进程状态
-        .manage(MiningManager::default())
-        // 3) 关闭窗口时，优雅停止 miner
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { .. } = event {
-                // 注意：不要把 window/app_handle/state 移入 tokio::spawn（会有 'static 生命周期要求）
-                // 这里同步阻塞一小下就行（应用要退出了）
-                let manager = window.app_handle().state::<MiningManager>();
-                tauri::async_runtime::block_on(async {
-                    let _ = manager.stop().await;
+        // 0) 单实例：再次启动时聚焦已有窗口，避免同时跑两个 miner（必须最先注册）
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            tray::show_main_window(app);
+        }))
+        // 1) 管理挖矿进程状态（心跳/远程通道与命令共用同一个实例）
+        .manage(Arc::new(MiningManager::default()))
+        // 2) 运行环境（store.dat + 前端事件）、ApiClient 与托盘：挖矿控制与实时算力
+        .setup(|app| {
+            let host = host::tauri_host(app.handle());
+            app.manage(host.clone());
+            // 上次选择的界面语言（后端错误与通知文案随之切换）
+            i18n::restore_locale(&host);
+            // 任意 API 响应中的版本门槛都经由该 host 落盘并通知前端；
+            // 最低版本提到当前版本之上时立即停挖，不等下一轮心跳
+            let stop_miner = app.state::<Arc<MiningManager>>().inner().clone();
+            let gate = version::VersionGate::new(host.clone()).on_unsupported(move || {
+                let miner = stop_miner.clone();
+                tauri::async_runtime::spawn(async move {
+                    if miner.is_running().await {
+                        let _ = miner.stop().await;
+                    }
                 });
+            });
+            app.manage(api::ApiClient::new().with_version_gate(gate));
+            // 上次下载的升级包已安装完成时清理
+            updater::clear_installed(&host);
+            // 远程配置：启动时拉取并定期刷新，变化后热更新到 MiningManager 与心跳
+            let api = app.state::<api::ApiClient>().api().clone();
+            let miner = app.state::<Arc<MiningManager>>().inner().clone();
+            tauri::async_runtime::spawn(remote_config::spawn_remote_config(api, host, miner));
+            tray::init(app.handle())?;
+            Ok(())
+        })
+        // 3) 关闭窗口：按用户设置隐藏到托盘（后台继续挖矿），或停止 miner 后退出
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let app = window.app_handle();
+                match settings::close_behavior(&app.state::<Host>()) {
+                    CloseBehavior::Background => {
+                        api.prevent_close();
+                        let _ = window.hide();
+                    }
+                    CloseBehavior::Quit => {
+                        // 注意：不要把 window/app_handle/state 移入 tokio::spawn（会有 'static 生命周期要求）
+                        // 这里同步阻塞一小下就行（应用要退出了）
+                        let manager = app.state::<Arc<MiningManager>>();
+                        tauri::async_runtime::block_on(async {
+                            let _ = manager.stop().await;
+                        });
+                    }
+                }
             }
         })
         // 4) 日志插件
-        .plugin(LogBuilder::new().targets(targets).build())
+        // 所有目标共用同一个 format，先脱敏再输出（钱包、token、邮箱等）
+        .plugin(
+            LogBuilder::new()
+                .targets(targets)
+                .max_file_size(diagnostics::LOG_MAX_FILE_SIZE)
+                .rotation_strategy(RotationStrategy::KeepSome(diagnostics::LOG_KEEP_FILES))
+                .format(|out, message, record| {
+                    let message = message.to_string();
+                    out.finish(format_args!(
+                        "[{}][{}] {}",
+                        record.level(),
+                        record.target(),
+                        redact::redact(&message)
+                    ))
+                })
+                .build(),
+        )
         // 5) Store 插件
         .plugin(tauri_plugin_store::Builder::default().build())
         // 6) 注册命令
@@ -47,15 +113,63 @@ fn main() {
             commands::login_by_code,
             commands::register,
             commands::send_code,
+            commands::reset_password,
             commands::get_auth_token,
             commands::logout,
+            commands::get_version_info,
+            commands::get_update_channel,
+            commands::set_update_channel,
+            commands::check_for_update,
+            commands::download_update,
+            commands::install_update,
+            commands::get_feature_flags,
+            commands::list_accounts,
+            commands::switch_account,
+            commands::remove_account,
+            commands::submit_2fa,
+            commands::get_2fa_status,
+            commands::enroll_totp,
+            commands::confirm_totp,
+            commands::disable_totp,
+            commands::regenerate_recovery_codes,
+            commands::start_device_login,
+            commands::wait_device_login,
+            // 个人资料 / 收款账户
+            commands::get_profile,
+            commands::update_profile,
+            commands::change_password,
+            commands::list_payout_accounts,
+            commands::save_payout_account,
+            commands::delete_payout_account,
+            // 邀请 / 推广
+            commands::get_invite_info,
+            commands::get_invited_users,
+            commands::get_referral_earnings,
             // 挖矿控制
             commands::start_cpu_mining,
             commands::stop_cpu_mining,
+            commands::pause_cpu_mining,
+            commands::resume_cpu_mining,
             commands::get_cpu_hashrate,
             // 前端状态查询
             commands::is_cpu_mining,
             commands::get_cpu_algo,
+            // 设置
+            commands::get_close_behavior,
+            commands::set_close_behavior,
+            // 诊断
+            commands::export_diagnostics,
+            // 语言
+            commands::set_locale,
+            commands::get_locale,
+            // 设备管理
+            commands::list_devices,
+            commands::rename_device,
+            commands::unbind_device,
+            commands::reregister_device,
+            commands_patch::cmd_bootstrap,
+            commands_patch::cmd_start_heartbeat,
+            commands_patch::cmd_start_remote,
         ])
         // 7) 运行
         .run(tauri::generate_context!())
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use hashtreasure_core::error::AppError;
+use hashtreasure_core::host::Host;
+
+const KEY_CLOSE_BEHAVIOR: &str = "close_behavior";
+
+/// 关闭主窗口时的行为
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseBehavior {
+    /// 隐藏到托盘，miner 与心跳在后台继续运行
+    #[default]
+    Background,
+    /// 停止 miner 并退出应用
+    Quit,
+}
+
+pub fn close_behavior(host: &Host) -> CloseBehavior {
+    host.store()
+        .get(KEY_CLOSE_BEHAVIOR)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_close_behavior(host: &Host, behavior: CloseBehavior) -> Result<(), AppError> {
+    host.store().set(KEY_CLOSE_BEHAVIOR, serde_json::to_value(behavior)?)
+}
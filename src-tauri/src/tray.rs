@@ -0,0 +1,149 @@
+use serde_json::json;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use std::sync::Arc;
+use tauri::{AppHandle, Listener, Manager, Wry};
+use hashtreasure_core::host::Host;
+use hashtreasure_core::i18n::tr;
+use hashtreasure_core::miner::MiningManager;
+
+const TRAY_ID: &str = "main";
+
+/// 需要随挖矿状态/语言更新的菜单项
+pub struct TrayMenu {
+    status: MenuItem<Wry>,
+    start: MenuItem<Wry>,
+    stop: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+    show: MenuItem<Wry>,
+    hide: MenuItem<Wry>,
+    quit: MenuItem<Wry>,
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app, "status", tr("trayIdle", &json!({})), false, None::<&str>)?;
+    let start = MenuItem::with_id(app, "start", tr("trayStart", &json!({})), true, None::<&str>)?;
+    let stop = MenuItem::with_id(app, "stop", tr("trayStop", &json!({})), false, None::<&str>)?;
+    let pause = MenuItem::with_id(app, "pause", tr("trayPause", &json!({})), false, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", tr("trayShow", &json!({})), true, None::<&str>)?;
+    let hide = MenuItem::with_id(app, "hide", tr("trayHide", &json!({})), true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", tr("trayQuit", &json!({})), true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &start,
+            &stop,
+            &pause,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &hide,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(tr("trayIdle", &json!({})))
+        .menu_on_left_click(false)
+        .on_menu_event(|app, event| {
+            let app = app.clone();
+            let id = event.id().as_ref().to_string();
+            tauri::async_runtime::spawn(async move { on_menu(&app, &id).await });
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    // 用彩色应用图标；不设 icon_as_template，否则 macOS 会把它渲染成纯色剪影
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    app.manage(TrayMenu { status, start, stop, pause, show, hide, quit });
+
+    // MiningManager 的状态与算力事件驱动托盘刷新
+    for event in ["miner:state", "cpu_hashrate", "cpu_algo"] {
+        let handle = app.clone();
+        app.listen_any(event, move |_| {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move { refresh(&handle).await });
+        });
+    }
+    Ok(())
+}
+
+async fn on_menu(app: &AppHandle, id: &str) {
+    let manager = app.state::<Arc<MiningManager>>();
+    let result = match id {
+        "start" => manager.start(app.state::<Host>().inner().clone()).await,
+        "stop" => manager.stop().await,
+        "pause" => {
+            if manager.stats().await.paused { manager.resume().await } else { manager.pause().await }
+        }
+        "show" => {
+            show_main_window(app);
+            Ok(())
+        }
+        "hide" => {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.hide();
+            }
+            Ok(())
+        }
+        "quit" => {
+            let _ = manager.stop().await;
+            app.exit(0);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        log::warn!("tray action {} failed: {}", id, e);
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window("main") {
+        let _ = w.show();
+        let _ = w.unminimize();
+        let _ = w.set_focus();
+    }
+}
+
+/// 按当前挖矿状态与语言刷新菜单文字、可用状态和 tooltip
+pub async fn refresh(app: &AppHandle) {
+    let Some(menu) = app.try_state::<TrayMenu>() else { return };
+    let stats = app.state::<Arc<MiningManager>>().stats().await;
+    let status = if !stats.running {
+        tr("trayIdle", &json!({}))
+    } else if stats.paused {
+        tr("trayPaused", &json!({}))
+    } else {
+        tr(
+            "trayHashrate",
+            &json!({
+                "hashrate": stats.hashrate.map(|h| format!("{:.1}", h)).unwrap_or_else(|| "-".into()),
+                "algo": stats.algo.clone().unwrap_or_else(|| "-".into()),
+            }),
+        )
+    };
+    let _ = menu.status.set_text(&status);
+    let _ = menu.start.set_text(tr("trayStart", &json!({})));
+    let _ = menu.start.set_enabled(!stats.running);
+    let _ = menu.stop.set_text(tr("trayStop", &json!({})));
+    let _ = menu.stop.set_enabled(stats.running);
+    let pause_key = if stats.paused { "trayResume" } else { "trayPause" };
+    let _ = menu.pause.set_text(tr(pause_key, &json!({})));
+    let _ = menu.pause.set_enabled(stats.running);
+    let _ = menu.show.set_text(tr("trayShow", &json!({})));
+    let _ = menu.hide.set_text(tr("trayHide", &json!({})));
+    let _ = menu.quit.set_text(tr("trayQuit", &json!({})));
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("HashTreasure – {}", status)));
+    }
+}